@@ -0,0 +1,266 @@
+use std::{
+    alloc::{self, Layout},
+    fmt,
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+use crate::*;
+
+/// A growable buffer whose address and capacity are aligned to a given
+/// alignment.
+///
+/// Direct IO, like `O_DIRECT` on Linux and `FILE_FLAG_NO_BUFFERING` on
+/// Windows, requires the address and length of the buffers to be aligned to
+/// the logical block size of the device, which is not guaranteed by [`Vec`].
+/// The capacity of `AlignedBuf` is always rounded up to a multiple of the
+/// alignment, so that it could be read into as a whole.
+///
+/// ```
+/// use compio_buf::{AlignedBuf, IoBuf};
+///
+/// let mut buf = AlignedBuf::with_capacity(100, 512);
+/// assert_eq!(buf.as_buf_ptr() as usize % 512, 0);
+/// assert_eq!(buf.capacity(), 512);
+///
+/// buf.extend_from_slice(b"hello");
+/// // Pad the contents to the alignment before a direct write.
+/// buf.resize(buf.align(), 0);
+/// assert_eq!(&buf[..5], b"hello");
+/// assert_eq!(buf.len(), 512);
+/// ```
+pub struct AlignedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    capacity: usize,
+    align: usize,
+}
+
+// SAFETY: the buffer owns the memory, like `Vec<u8>`.
+unsafe impl Send for AlignedBuf {}
+unsafe impl Sync for AlignedBuf {}
+
+impl AlignedBuf {
+    /// Create an empty buffer with the alignment, without allocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two.
+    pub fn new(align: usize) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "alignment should be a power of two"
+        );
+        Self {
+            // A dangling pointer which is aligned.
+            ptr: NonNull::new(ptr::without_provenance_mut(align)).unwrap(),
+            len: 0,
+            capacity: 0,
+            align,
+        }
+    }
+
+    /// Create an empty buffer with at least the specified capacity, rounded
+    /// up to a multiple of the alignment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two, or the capacity overflows.
+    pub fn with_capacity(capacity: usize, align: usize) -> Self {
+        let mut buf = Self::new(align);
+        buf.reserve(capacity);
+        buf
+    }
+
+    /// Create a buffer of `len` zeroed bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two, or the capacity overflows.
+    pub fn zeroed(len: usize, align: usize) -> Self {
+        let mut buf = Self::with_capacity(len, align);
+        buf.resize(len, 0);
+        buf
+    }
+
+    /// The alignment of the address and capacity.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    /// The number of initialized bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// If there are no initialized bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The total number of bytes the buffer could hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Reserve capacity for at least `additional` more bytes. The capacity is
+    /// rounded up to a multiple of the alignment, and the contents are moved
+    /// if it is reallocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity overflows.
+    pub fn reserve(&mut self, additional: usize) {
+        let required = self.len.checked_add(additional).expect("capacity overflow");
+        if required <= self.capacity {
+            return;
+        }
+        // Grow at least twice as `Vec`, to amortize the reallocations.
+        let capacity = required
+            .max(self.capacity.saturating_mul(2))
+            .checked_next_multiple_of(self.align)
+            .expect("capacity overflow");
+        let layout = Layout::from_size_align(capacity, self.align).expect("capacity overflow");
+        let ptr = if self.capacity == 0 {
+            unsafe { alloc::alloc(layout) }
+        } else {
+            unsafe { alloc::realloc(self.ptr.as_ptr(), self.layout(), capacity) }
+        };
+        self.ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        self.capacity = capacity;
+    }
+
+    /// Shorten the buffer, keeping the first `len` bytes. It has no effect if
+    /// `len` is greater than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Clear the buffer, without changing the capacity.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Resize the buffer in place so that its length is `len`. The new bytes
+    /// are filled with `value`.
+    pub fn resize(&mut self, len: usize, value: u8) {
+        if len > self.len {
+            self.reserve(len - self.len);
+            unsafe {
+                self.ptr
+                    .as_ptr()
+                    .add(self.len)
+                    .write_bytes(value, len - self.len)
+            };
+        }
+        self.len = len;
+    }
+
+    /// Append the bytes to the buffer.
+    pub fn extend_from_slice(&mut self, other: &[u8]) {
+        self.reserve(other.len());
+        unsafe {
+            self.ptr
+                .as_ptr()
+                .add(self.len)
+                .copy_from_nonoverlapping(other.as_ptr(), other.len())
+        };
+        self.len += other.len();
+    }
+
+    /// Force the length of the buffer to `len`.
+    ///
+    /// # Safety
+    ///
+    /// `len` should be less than or equal to the capacity, and the bytes
+    /// before `len` should be initialized.
+    pub unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity);
+        self.len = len;
+    }
+
+    fn layout(&self) -> Layout {
+        // SAFETY: the layout is checked when the buffer is allocated.
+        unsafe { Layout::from_size_align_unchecked(self.capacity, self.align) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        if self.capacity > 0 {
+            unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout()) }
+        }
+    }
+}
+
+impl Clone for AlignedBuf {
+    fn clone(&self) -> Self {
+        let mut buf = Self::with_capacity(self.len, self.align);
+        buf.extend_from_slice(self);
+        buf
+    }
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl AsRef<[u8]> for AlignedBuf {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for AlignedBuf {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl fmt::Debug for AlignedBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBuf")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .field("align", &self.align)
+            .finish_non_exhaustive()
+    }
+}
+
+impl IoBuf for AlignedBuf {
+    fn as_buf_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    fn buf_len(&self) -> usize {
+        self.len
+    }
+
+    fn buf_capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl IoBufMut for AlignedBuf {
+    fn as_buf_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+}
+
+impl SetBufInit for AlignedBuf {
+    unsafe fn set_buf_init(&mut self, len: usize) {
+        if self.len < len {
+            self.set_len(len);
+        }
+    }
+}
@@ -32,6 +32,9 @@ pub use slice::*;
 mod iter;
 pub use iter::*;
 
+mod aligned;
+pub use aligned::*;
+
 /// Trait to get the inner buffer of an operation or a result.
 pub trait IntoInner {
     /// The inner type.
@@ -0,0 +1,205 @@
+use std::io;
+
+/// The strategies to pin the worker threads to the CPUs automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreAffinity {
+    /// Pin each worker thread to a single CPU. The CPUs available to the
+    /// process are assigned in order, grouped by NUMA node, and are reused if
+    /// there are more workers than CPUs.
+    Cores,
+    /// Pin each worker thread to all CPUs of a NUMA node. The workers are
+    /// divided evenly into the nodes, and the workers with consecutive indices
+    /// share a node. If the NUMA topology is not available, all CPUs are
+    /// treated as one node.
+    NumaNodes,
+}
+
+impl CoreAffinity {
+    // Assign the CPU sets to the workers.
+    pub(crate) fn assign(self, nthreads: usize) -> io::Result<Vec<Vec<usize>>> {
+        let nodes = numa_nodes()?;
+        let sets = match self {
+            Self::Cores => {
+                let cpus = nodes.into_iter().flatten().collect::<Vec<_>>();
+                (0..nthreads)
+                    .map(|index| vec![cpus[index % cpus.len()]])
+                    .collect()
+            }
+            Self::NumaNodes => (0..nthreads)
+                .map(|index| nodes[index * nodes.len() / nthreads].clone())
+                .collect(),
+        };
+        Ok(sets)
+    }
+}
+
+// The CPUs available to the process, grouped by NUMA node.
+fn numa_nodes() -> io::Result<Vec<Vec<usize>>> {
+    let cpus = sys::available_cpus()?;
+    if cpus.is_empty() {
+        return Err(io::Error::other("no CPU is available"));
+    }
+    let nodes = sys::numa_nodes()
+        .into_iter()
+        .map(|node| {
+            node.into_iter()
+                .filter(|cpu| cpus.contains(cpu))
+                .collect::<Vec<_>>()
+        })
+        .filter(|node| !node.is_empty())
+        .collect::<Vec<_>>();
+    // Treat all CPUs as one node if the nodes don't cover them.
+    if nodes.iter().map(Vec::len).sum::<usize>() == cpus.len() {
+        Ok(nodes)
+    } else {
+        Ok(vec![cpus])
+    }
+}
+
+/// Pin the current thread to the CPU set.
+pub(crate) fn set_current(cpus: &[usize]) -> io::Result<()> {
+    if cpus.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the CPU set is empty",
+        ));
+    }
+    sys::set_current(cpus)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sys {
+    use std::{fs, io, mem};
+
+    fn cpu_out_of_range(cpu: usize) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("CPU {cpu} is out of range"),
+        )
+    }
+
+    pub fn available_cpus() -> io::Result<Vec<usize>> {
+        let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+        let res =
+            unsafe { libc::sched_getaffinity(0, mem::size_of::<libc::cpu_set_t>(), &mut set) };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((0..libc::CPU_SETSIZE as usize)
+            .filter(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) })
+            .collect())
+    }
+
+    pub fn set_current(cpus: &[usize]) -> io::Result<()> {
+        let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+        for &cpu in cpus {
+            if cpu >= libc::CPU_SETSIZE as usize {
+                return Err(cpu_out_of_range(cpu));
+            }
+            unsafe { libc::CPU_SET(cpu, &mut set) };
+        }
+        let res = unsafe { libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    // Read the NUMA nodes from sysfs. Returns an empty list if it fails.
+    pub fn numa_nodes() -> Vec<Vec<usize>> {
+        let Ok(dir) = fs::read_dir("/sys/devices/system/node") else {
+            return vec![];
+        };
+        let mut nodes = dir
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let id = entry
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("node")?
+                    .parse::<usize>()
+                    .ok()?;
+                let list = fs::read_to_string(entry.path().join("cpulist")).ok()?;
+                Some((id, parse_cpu_list(list.trim())?))
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|(id, _)| *id);
+        nodes.into_iter().map(|(_, cpus)| cpus).collect()
+    }
+
+    // Parse a CPU list like `0-3,8-11`.
+    fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+        let mut cpus = vec![];
+        for range in list.split(',').filter(|s| !s.is_empty()) {
+            match range.split_once('-') {
+                Some((start, end)) => cpus.extend(start.parse::<usize>().ok()?..=end.parse().ok()?),
+                None => cpus.push(range.parse().ok()?),
+            }
+        }
+        Some(cpus)
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::io;
+
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentProcess, GetCurrentThread, GetProcessAffinityMask, SetThreadAffinityMask,
+    };
+
+    pub fn available_cpus() -> io::Result<Vec<usize>> {
+        let mut process_mask = 0;
+        let mut system_mask = 0;
+        let res = unsafe {
+            GetProcessAffinityMask(GetCurrentProcess(), &mut process_mask, &mut system_mask)
+        };
+        if res == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((0..usize::BITS as usize)
+            .filter(|cpu| process_mask & (1 << cpu) != 0)
+            .collect())
+    }
+
+    pub fn set_current(cpus: &[usize]) -> io::Result<()> {
+        let mut mask = 0usize;
+        for &cpu in cpus {
+            if cpu >= usize::BITS as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("CPU {cpu} is out of range"),
+                ));
+            }
+            mask |= 1 << cpu;
+        }
+        if unsafe { SetThreadAffinityMask(GetCurrentThread(), mask) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub fn numa_nodes() -> Vec<Vec<usize>> {
+        vec![]
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+mod sys {
+    use std::{io, thread::available_parallelism};
+
+    pub fn available_cpus() -> io::Result<Vec<usize>> {
+        Ok((0..available_parallelism()?.get()).collect())
+    }
+
+    pub fn set_current(_cpus: &[usize]) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "CPU affinity is not supported on this platform",
+        ))
+    }
+
+    pub fn numa_nodes() -> Vec<Vec<usize>> {
+        vec![]
+    }
+}
@@ -2,56 +2,181 @@
 
 #![warn(missing_docs)]
 
+mod affinity;
+#[cfg(all(
+    unix,
+    not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+))]
+mod server;
+mod worker;
+
 use std::{
+    cell::Cell,
     future::Future,
+    hash::{DefaultHasher, Hash, Hasher},
     io,
     num::NonZeroUsize,
-    panic::resume_unwind,
-    thread::{available_parallelism, JoinHandle},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    thread::{self, available_parallelism},
+    time::{Duration, Instant},
 };
 
-use crossbeam_channel::{unbounded, SendError, Sender};
+pub use affinity::CoreAffinity;
+use compio_runtime::{channel::mpsc, JoinError};
+use compio_sync::Semaphore;
+use futures_channel::oneshot;
 use futures_util::{future::LocalBoxFuture, FutureExt};
+#[cfg(all(
+    unix,
+    not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+))]
+pub use server::TcpServer;
+use worker::{run_worker, Worker, WorkerState};
+pub use worker::{WorkerMetrics, WorkerReport};
+
+type BoxClosure<'a> = Box<dyn (FnOnce() -> LocalBoxFuture<'a, ()>) + Send>;
+
+thread_local! {
+    static WORKER_INDEX: Cell<Option<usize>> = const { Cell::new(None) };
+}
 
-type BoxClosure<'a> = Box<dyn (FnOnce() -> LocalBoxFuture<'a, io::Result<()>>) + Send>;
+/// The index of the current worker thread, if called on a worker thread of a
+/// [`Dispatcher`]. It could be used to shard the per-core states.
+///
+/// ```
+/// use compio_dispatcher::Dispatcher;
+///
+/// let dispatcher = Dispatcher::new().unwrap();
+/// let handle = dispatcher
+///     .dispatch(|| async { compio_dispatcher::worker_index() })
+///     .unwrap();
+/// assert!(futures_executor::block_on(handle).unwrap().is_some());
+/// assert_eq!(compio_dispatcher::worker_index(), None);
+/// for res in dispatcher.join() {
+///     res.unwrap();
+/// }
+/// ```
+pub fn worker_index() -> Option<usize> {
+    WORKER_INDEX.with(|index| index.get())
+}
+
+/// The policy to select the worker thread for [`Dispatcher::dispatch`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchPolicy {
+    /// Select the workers in turn.
+    RoundRobin,
+    /// Select the worker with the fewest in-flight tasks.
+    #[default]
+    LeastLoaded,
+    /// Select two workers randomly, and pick the one with fewer in-flight
+    /// tasks.
+    PowerOfTwoChoices,
+}
 
 /// The dispatcher. It manages the threads and dispatches the tasks.
+///
+/// Each worker thread runs a runtime, and the tasks dispatched to it run
+/// concurrently.
 pub struct Dispatcher {
-    sender: Sender<BoxClosure<'static>>,
-    threads: Vec<JoinHandle<io::Result<()>>>,
+    workers: Vec<Worker>,
+    threads: Vec<thread::JoinHandle<()>>,
+    policy: DispatchPolicy,
+    // Increased by every selection, to choose the workers in turn or
+    // randomly.
+    next: AtomicUsize,
 }
 
 impl Dispatcher {
     /// Create the dispatcher with specified number of threads.
     pub(crate) fn new_impl(mut builder: DispatcherBuilder) -> io::Result<Self> {
-        let (sender, receiver) = unbounded::<BoxClosure<'static>>();
-        let threads = (0..builder.nthreads)
-            .map({
-                |index| {
-                    let receiver = receiver.clone();
-
-                    let thread_builder = std::thread::Builder::new();
-                    let thread_builder = if let Some(s) = builder.stack_size {
-                        thread_builder.stack_size(s)
-                    } else {
-                        thread_builder
-                    };
-                    let thread_builder = if let Some(f) = &mut builder.names {
-                        thread_builder.name(f(index))
-                    } else {
-                        thread_builder
-                    };
+        let max_tasks = builder
+            .max_concurrent_tasks
+            // The semaphore of the worker cannot hold more permits.
+            .map(|n| n.get().min(Semaphore::MAX_PERMITS))
+            .unwrap_or(Semaphore::MAX_PERMITS);
+        let mut cpu_sets = match &builder.affinity {
+            Some(Affinity::Auto(affinity)) => Some(affinity.assign(builder.nthreads)?),
+            _ => None,
+        };
+        let mut workers = Vec::with_capacity(builder.nthreads);
+        let mut threads = Vec::with_capacity(builder.nthreads);
+        for index in 0..builder.nthreads {
+            let thread_builder = std::thread::Builder::new();
+            let thread_builder = if let Some(s) = builder.stack_size {
+                thread_builder.stack_size(s)
+            } else {
+                thread_builder
+            };
+            let thread_builder = if let Some(f) = &mut builder.names {
+                thread_builder.name(f(index))
+            } else {
+                thread_builder
+            };
 
-                    thread_builder.spawn(move || {
-                        while let Ok(f) = receiver.recv() {
-                            compio_runtime::block_on(f())?;
+            let cpus = match &mut builder.affinity {
+                Some(Affinity::Auto(_)) => cpu_sets
+                    .as_mut()
+                    .map(|sets| std::mem::take(&mut sets[index])),
+                Some(Affinity::Custom(f)) => Some(f(index)),
+                None => None,
+            };
+
+            let state = Arc::new(WorkerState::new(builder.queue_capacity.map(|n| n.get())));
+            // The receiver is bound to the runtime of the worker thread, so the
+            // channel is created there.
+            let (tx, rx) = std::sync::mpsc::sync_channel(1);
+            let (stop, stop_rx) = oneshot::channel();
+            let (report_tx, report) = oneshot::channel();
+            let cancel_timeout = builder.cancel_timeout;
+            let thread = thread_builder.spawn({
+                let state = state.clone();
+                move || {
+                    if let Some(cpus) = cpus {
+                        if let Err(e) = affinity::set_current(&cpus) {
+                            tx.send(Err(e)).ok();
+                            return;
+                        }
+                    }
+                    WORKER_INDEX.with(|i| i.set(Some(index)));
+                    let receiver = match mpsc::unbounded() {
+                        Ok((sender, receiver)) => {
+                            tx.send(Ok(sender)).ok();
+                            receiver
                         }
-                        Ok(())
-                    })
+                        Err(e) => {
+                            tx.send(Err(e)).ok();
+                            return;
+                        }
+                    };
+                    let graceful =
+                        compio_runtime::block_on(run_worker(receiver, state, max_tasks, stop_rx));
+                    // Cancel the tasks not completed before the deadline.
+                    let runtime = compio_runtime::shutdown(cancel_timeout);
+                    report_tx.send(WorkerReport::new(graceful, runtime)).ok();
                 }
-            })
-            .collect::<io::Result<Vec<_>>>()?;
-        Ok(Self { sender, threads })
+            })?;
+            let sender = rx
+                .recv()
+                .map_err(|_| io::Error::other("the worker thread panicked"))??;
+            workers.push(Worker {
+                sender,
+                state,
+                stop,
+                report,
+            });
+            threads.push(thread);
+        }
+        Ok(Self {
+            workers,
+            threads,
+            policy: builder.policy,
+            next: AtomicUsize::new(0),
+        })
     }
 
     /// Create the dispatcher with default config.
@@ -68,31 +193,419 @@ impl Dispatcher {
     ///
     /// The provided `f` should be [`Send`] because it will be send to another
     /// thread before calling. The return [`Future`] need not to be [`Send`]
-    /// because it will be executed on only one thread.
-    pub fn dispatch<F: Future<Output = io::Result<()>> + 'static>(
+    /// because it will be executed on only one thread. The output should be
+    /// [`Send`] because it is sent back through the returned [`JoinHandle`].
+    ///
+    /// The worker is selected by the [`DispatchPolicy`]. It fails with
+    /// [`io::ErrorKind::WouldBlock`] if the queue of the worker is full, see
+    /// [`Dispatcher::dispatch_wait`] to wait for the space instead.
+    ///
+    /// ```
+    /// use compio_dispatcher::Dispatcher;
+    ///
+    /// let dispatcher = Dispatcher::new().unwrap();
+    /// let handle = dispatcher.dispatch(|| async { 42 }).unwrap();
+    /// // The handle could be awaited on any thread with any executor.
+    /// let res = futures_executor::block_on(handle);
+    /// assert_eq!(res.unwrap(), 42);
+    /// for res in dispatcher.join() {
+    ///     res.unwrap();
+    /// }
+    /// ```
+    pub fn dispatch<F, T>(
+        &self,
+        f: impl (FnOnce() -> F) + Send + 'static,
+    ) -> io::Result<JoinHandle<T>>
+    where
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        self.dispatch_on(self.select_worker(), f)
+    }
+
+    /// Dispatch a task to the threads like [`Dispatcher::dispatch`], but wait
+    /// asynchronously if the queue of the selected worker is full. The
+    /// returned future is [`Send`], and could be awaited on any thread.
+    ///
+    /// ```
+    /// use std::num::NonZeroUsize;
+    ///
+    /// use compio_dispatcher::Dispatcher;
+    ///
+    /// let dispatcher = Dispatcher::builder()
+    ///     .queue_capacity(NonZeroUsize::new(1).unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// futures_executor::block_on(async {
+    ///     let handles = futures_util::future::try_join_all(
+    ///         (0..10).map(|i| dispatcher.dispatch_wait(move || async move { i })),
+    ///     )
+    ///     .await
+    ///     .unwrap();
+    ///     for (i, handle) in handles.into_iter().enumerate() {
+    ///         assert_eq!(handle.await.unwrap(), i);
+    ///     }
+    /// });
+    /// for res in dispatcher.join() {
+    ///     res.unwrap();
+    /// }
+    /// ```
+    pub async fn dispatch_wait<F, T>(
         &self,
         f: impl (FnOnce() -> F) + Send + 'static,
-    ) -> Result<(), SendError<BoxClosure<'static>>> {
-        self.sender
-            .send(Box::new(move || f().boxed_local()) as BoxClosure<'static>)
+    ) -> io::Result<JoinHandle<T>>
+    where
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        let worker = self.select_worker();
+        worker.state.reserve().await;
+        self.send_reserved(worker, f)
     }
 
-    /// Stop the dispatcher and wait for the threads to complete. If there is a
-    /// thread panicked, this method will resume the panic.
+    /// Dispatch a task to the worker thread with the index. Returns an error
+    /// if the index is out of range.
+    ///
+    /// See [`Dispatcher::dispatch`] for the requirements of `f`.
+    pub fn dispatch_to<F, T>(
+        &self,
+        index: usize,
+        f: impl (FnOnce() -> F) + Send + 'static,
+    ) -> io::Result<JoinHandle<T>>
+    where
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        let worker = self.workers.get(index).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("worker index {index} is out of range"),
+            )
+        })?;
+        self.dispatch_on(worker, f)
+    }
+
+    /// Dispatch a task to the worker thread decided by the hash of the key.
+    /// The tasks with the same key are always dispatched to the same worker,
+    /// so that the related tasks, like the ones of a session, could share the
+    /// per-thread states.
+    ///
+    /// See [`Dispatcher::dispatch`] for the requirements of `f`.
+    ///
+    /// ```
+    /// use compio_dispatcher::{worker_index, Dispatcher};
+    ///
+    /// let dispatcher = Dispatcher::new().unwrap();
+    /// let a = dispatcher.dispatch_by_key(&"session", || async { worker_index() });
+    /// let b = dispatcher.dispatch_by_key(&"session", || async { worker_index() });
+    /// let a = futures_executor::block_on(a.unwrap()).unwrap();
+    /// let b = futures_executor::block_on(b.unwrap()).unwrap();
+    /// assert_eq!(a, b);
+    /// for res in dispatcher.join() {
+    ///     res.unwrap();
+    /// }
+    /// ```
+    pub fn dispatch_by_key<K: Hash + ?Sized, F, T>(
+        &self,
+        key: &K,
+        f: impl (FnOnce() -> F) + Send + 'static,
+    ) -> io::Result<JoinHandle<T>>
+    where
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        // The default hasher is created with fixed keys, so the routing is
+        // consistent.
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let index = (hasher.finish() % self.workers.len() as u64) as usize;
+        self.dispatch_on(&self.workers[index], f)
+    }
+
+    /// Run the task on every worker thread, e.g., to initialize the
+    /// per-thread states. The handles are returned in the order of the worker
+    /// indices.
+    ///
+    /// See [`Dispatcher::dispatch`] for the requirements of `f`. It should be
+    /// [`Sync`] because it is shared by the workers. A slot is reserved in the
+    /// queue of every worker before the task is sent, so that it fails without
+    /// running the task anywhere if any of the queues is full.
+    pub fn broadcast<F, T>(
+        &self,
+        f: impl (Fn() -> F) + Send + Sync + 'static,
+    ) -> io::Result<Vec<JoinHandle<T>>>
+    where
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        let reserved = self
+            .workers
+            .iter()
+            .take_while(|worker| worker.state.try_reserve())
+            .count();
+        if reserved < self.workers.len() {
+            self.workers[..reserved]
+                .iter()
+                .for_each(|worker| worker.state.cancel());
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "the queue of a worker is full",
+            ));
+        }
+        let f = Arc::new(f);
+        let mut handles = Vec::with_capacity(self.workers.len());
+        for (i, worker) in self.workers.iter().enumerate() {
+            let f = f.clone();
+            match self.send_reserved(worker, move || f()) {
+                Ok(handle) => handles.push(handle),
+                Err(e) => {
+                    // The worker thread has exited. Release the reservations not used.
+                    self.workers[i + 1..]
+                        .iter()
+                        .for_each(|worker| worker.state.cancel());
+                    return Err(e);
+                }
+            }
+        }
+        Ok(handles)
+    }
+
+    /// The number of the worker threads.
+    pub fn worker_threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// The metrics of the worker threads, in the order of the worker indices.
+    pub fn metrics(&self) -> Vec<WorkerMetrics> {
+        self.workers
+            .iter()
+            .map(|worker| worker.state.metrics())
+            .collect()
+    }
+
+    // Dispatch the task to the worker, or fail if the queue is full.
+    fn dispatch_on<F, T>(
+        &self,
+        worker: &Worker,
+        f: impl (FnOnce() -> F) + Send + 'static,
+    ) -> io::Result<JoinHandle<T>>
+    where
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        if !worker.state.try_reserve() {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "the queue of the worker is full",
+            ));
+        }
+        self.send_reserved(worker, f)
+    }
+
+    // Send the task to the worker, after a slot in the queue is reserved.
+    fn send_reserved<F, T>(
+        &self,
+        worker: &Worker,
+        f: impl (FnOnce() -> F) + Send + 'static,
+    ) -> io::Result<JoinHandle<T>>
+    where
+        F: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let closure = move || {
+            async move {
+                // Call `f` in the spawned task so that its panic is caught, too.
+                let res = compio_runtime::spawn(async move { f().await }).await;
+                tx.send(res).ok();
+            }
+            .boxed_local()
+        };
+        if worker
+            .sender
+            .send(Box::new(closure) as BoxClosure<'static>)
+            .is_err()
+        {
+            worker.state.cancel();
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "the dispatcher is stopped",
+            ));
+        }
+        worker.state.on_dispatched();
+        Ok(JoinHandle { rx })
+    }
+
+    // Select the worker by the policy.
+    fn select_worker(&self) -> &Worker {
+        let len = self.workers.len();
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+        let index = match self.policy {
+            DispatchPolicy::RoundRobin => next % len,
+            // The search starts from a different worker every time, so that
+            // the tasks are dispatched in turn when the workers are equally
+            // busy.
+            DispatchPolicy::LeastLoaded => (0..len)
+                .map(|i| (next + i) % len)
+                .min_by_key(|i| self.workers[*i].state.in_flight())
+                .expect("there should be at least one worker"),
+            DispatchPolicy::PowerOfTwoChoices => {
+                // Hash the counter to get a pseudo random number.
+                let mut hasher = DefaultHasher::new();
+                next.hash(&mut hasher);
+                let random = hasher.finish() as usize;
+                let a = random % len;
+                let b = if len > 1 {
+                    (a + 1 + (random >> 16) % (len - 1)) % len
+                } else {
+                    a
+                };
+                if self.workers[a].state.in_flight() <= self.workers[b].state.in_flight() {
+                    a
+                } else {
+                    b
+                }
+            }
+        };
+        &self.workers[index]
+    }
+
+    /// Stop the dispatcher and wait for the threads to complete the dispatched
+    /// tasks. The panics of the tasks are returned by their [`JoinHandle`]s.
+    /// The results of the threads are returned in the order of the worker
+    /// indices.
     pub fn join(self) -> Vec<io::Result<()>> {
-        drop(self.sender);
+        drop(self.workers);
         self.threads
             .into_iter()
-            .map(|thread| thread.join().unwrap_or_else(|e| resume_unwind(e)))
+            .map(|thread| {
+                thread
+                    .join()
+                    .map_err(|_| io::Error::other("the worker thread panicked"))
+            })
+            .collect()
+    }
+
+    /// Shut down the dispatcher gracefully, and wait for the threads to exit.
+    ///
+    /// The dispatcher stops accepting new tasks. The queued and running tasks
+    /// could run until the `deadline`, and then they are cancelled by shutting
+    /// down the runtimes of the workers, see
+    /// [`compio_runtime::shutdown`]. The runtimes wait for the cancelled
+    /// operations at most the [cancel
+    /// timeout](DispatcherBuilder::cancel_timeout). The [`JoinHandle`]s of the
+    /// cancelled tasks resolve to cancelled [`JoinError`]s.
+    ///
+    /// The reports are returned in the order of the worker indices. It blocks
+    /// the current thread, see [`Dispatcher::shutdown_async`] for the async
+    /// variant.
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use compio_dispatcher::Dispatcher;
+    ///
+    /// let dispatcher = Dispatcher::new().unwrap();
+    /// let handle = dispatcher
+    ///     .dispatch(|| std::future::pending::<()>())
+    ///     .unwrap();
+    /// let reports = dispatcher.shutdown(Instant::now() + Duration::from_millis(10));
+    /// assert!(reports
+    ///     .iter()
+    ///     .any(|report| !report.as_ref().unwrap().graceful));
+    /// let err = futures_executor::block_on(handle).unwrap_err();
+    /// assert!(err.is_cancelled());
+    /// ```
+    pub fn shutdown(self, deadline: Instant) -> Vec<io::Result<WorkerReport>> {
+        self.stop(deadline)
+            .into_iter()
+            .map(|(mut report, thread)| {
+                let joined = thread.join();
+                match report.try_recv() {
+                    Ok(Some(report)) => Ok(report),
+                    _ if joined.is_err() => Err(io::Error::other("the worker thread panicked")),
+                    _ => Err(io::Error::other("the worker thread exited unexpectedly")),
+                }
+            })
+            .collect()
+    }
+
+    /// Shut down the dispatcher gracefully like [`Dispatcher::shutdown`], and
+    /// wait for the threads asynchronously. It could be awaited in another
+    /// runtime, including a compio one.
+    pub async fn shutdown_async(self, deadline: Instant) -> Vec<io::Result<WorkerReport>> {
+        let workers = self.stop(deadline);
+        let mut results = Vec::with_capacity(workers.len());
+        for (report, thread) in workers {
+            let report = report.await;
+            // The thread exits right after sending the report, so it won't block
+            // for long.
+            let joined = thread.join();
+            results.push(match report {
+                Ok(report) => Ok(report),
+                Err(_) if joined.is_err() => Err(io::Error::other("the worker thread panicked")),
+                Err(_) => Err(io::Error::other("the worker thread exited unexpectedly")),
+            });
+        }
+        results
+    }
+
+    // Stop accepting tasks, and send the deadline to the workers. Returns the
+    // receivers of the reports with the threads.
+    fn stop(
+        self,
+        deadline: Instant,
+    ) -> Vec<(oneshot::Receiver<WorkerReport>, thread::JoinHandle<()>)> {
+        self.workers
+            .into_iter()
+            .zip(self.threads)
+            .map(|(worker, thread)| {
+                worker.stop.send(deadline).ok();
+                (worker.report, thread)
+            })
             .collect()
     }
 }
 
+/// A handle to a dispatched task, returned by [`Dispatcher::dispatch`].
+///
+/// Awaiting the handle gives the output of the task, or a [`JoinError`] if
+/// the task panicked, or was cancelled by [`Dispatcher::shutdown`]. It is
+/// [`Send`], and could be awaited on any thread, even if there is no compio
+/// runtime. Dropping the handle doesn't cancel the task.
+#[must_use = "the output of the task is lost if the handle is dropped"]
+#[derive(Debug)]
+pub struct JoinHandle<T> {
+    rx: oneshot::Receiver<Result<T, JoinError>>,
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The sender is dropped without sending if the task is cancelled by
+        // the shutdown.
+        self.rx
+            .poll_unpin(cx)
+            .map(|res| res.unwrap_or_else(|_| Err(JoinError::cancelled())))
+    }
+}
+
 /// A builder for [`Dispatcher`].
 pub struct DispatcherBuilder {
     nthreads: usize,
     stack_size: Option<usize>,
     names: Option<Box<dyn FnMut(usize) -> String>>,
+    max_concurrent_tasks: Option<NonZeroUsize>,
+    affinity: Option<Affinity>,
+    queue_capacity: Option<NonZeroUsize>,
+    policy: DispatchPolicy,
+    cancel_timeout: Duration,
+}
+
+enum Affinity {
+    Auto(CoreAffinity),
+    Custom(Box<dyn FnMut(usize) -> Vec<usize>>),
 }
 
 impl DispatcherBuilder {
@@ -102,6 +615,11 @@ impl DispatcherBuilder {
             nthreads: available_parallelism().map(|n| n.get()).unwrap_or(1),
             stack_size: None,
             names: None,
+            max_concurrent_tasks: None,
+            affinity: None,
+            queue_capacity: None,
+            policy: DispatchPolicy::default(),
+            cancel_timeout: Duration::from_secs(1),
         }
     }
 
@@ -125,6 +643,58 @@ impl DispatcherBuilder {
         self
     }
 
+    /// Set the max number of tasks running concurrently on each worker thread.
+    /// The tasks dispatched to a worker over the limit wait for the running
+    /// ones to complete. The default is unlimited, and so is any value above
+    /// [`Semaphore::MAX_PERMITS`].
+    pub fn max_concurrent_tasks(mut self, n: NonZeroUsize) -> Self {
+        self.max_concurrent_tasks = Some(n);
+        self
+    }
+
+    /// Set the capacity of the queue of each worker thread. The tasks are
+    /// queued when the worker is running [`max_concurrent_tasks`] tasks, and
+    /// the dispatching fails or waits when the queue is full, so that at most
+    /// `max_concurrent_tasks + capacity` tasks are in flight on a worker. The
+    /// default is unbounded.
+    ///
+    /// [`max_concurrent_tasks`]: DispatcherBuilder::max_concurrent_tasks
+    pub fn queue_capacity(mut self, n: NonZeroUsize) -> Self {
+        self.queue_capacity = Some(n);
+        self
+    }
+
+    /// Set the policy to select the worker thread. The default is
+    /// [`DispatchPolicy::LeastLoaded`].
+    pub fn dispatch_policy(mut self, policy: DispatchPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the max time to wait for the cancelled operations when the
+    /// dispatcher is [shut down](Dispatcher::shutdown). The buffers of the
+    /// operations not completed in time are leaked. The default value is 1
+    /// second.
+    pub fn cancel_timeout(mut self, timeout: Duration) -> Self {
+        self.cancel_timeout = timeout;
+        self
+    }
+
+    /// Pin the worker threads to the CPUs automatically with the strategy.
+    /// The building fails if it is not supported on the platform.
+    pub fn core_affinity(mut self, affinity: CoreAffinity) -> Self {
+        self.affinity = Some(Affinity::Auto(affinity));
+        self
+    }
+
+    /// Provide a function to assign the CPU set to the worker threads by
+    /// index. The building fails if the CPU set is empty or invalid, or it is
+    /// not supported on the platform.
+    pub fn core_affinity_with(mut self, f: impl (FnMut(usize) -> Vec<usize>) + 'static) -> Self {
+        self.affinity = Some(Affinity::Custom(Box::new(f)));
+        self
+    }
+
     /// Build the [`Dispatcher`].
     pub fn build(self) -> io::Result<Dispatcher> {
         Dispatcher::new_impl(self)
@@ -0,0 +1,205 @@
+use std::{cell::Cell, future::Future, io, net::SocketAddr, pin::pin, rc::Rc, sync::Arc};
+
+use compio_net::{TcpListener, TcpStream};
+use compio_sync::Notify;
+use futures_channel::oneshot;
+use futures_util::future::{select, Either};
+
+use crate::{Dispatcher, JoinHandle};
+
+impl Dispatcher {
+    /// Serve TCP connections on all worker threads.
+    ///
+    /// A [`TcpListener`] is bound to `addr` with `SO_REUSEPORT` on each
+    /// worker, and the kernel distributes the incoming connections among
+    /// them. Each worker runs an accept loop, and spawns `handler` on itself
+    /// for every connection. If the port of `addr` is 0, the port assigned to
+    /// the first worker is used by the others.
+    ///
+    /// The returned future is [`Send`], and could be awaited on any thread.
+    /// The server stops accepting if the returned [`TcpServer`] is dropped.
+    ///
+    /// ```
+    /// use compio_dispatcher::Dispatcher;
+    /// use compio_io::{AsyncReadExt, AsyncWriteExt};
+    /// use compio_net::TcpStream;
+    ///
+    /// let dispatcher = Dispatcher::new().unwrap();
+    /// compio_runtime::block_on(async {
+    ///     let server = dispatcher
+    ///         .serve_tcp("127.0.0.1:0".parse().unwrap(), |mut stream, _| async move {
+    ///             stream.write_all("hello").await.0.unwrap();
+    ///         })
+    ///         .await
+    ///         .unwrap();
+    ///     let mut client = TcpStream::connect(server.local_addr()).await.unwrap();
+    ///     let (_, buf) = client.read_exact(Vec::with_capacity(5)).await.unwrap();
+    ///     assert_eq!(buf, b"hello");
+    ///     server.shutdown().await.unwrap();
+    /// });
+    /// for res in dispatcher.join() {
+    ///     res.unwrap();
+    /// }
+    /// ```
+    pub async fn serve_tcp<H, F>(&self, addr: SocketAddr, handler: H) -> io::Result<TcpServer>
+    where
+        H: (Fn(TcpStream, SocketAddr) -> F) + Send + Sync + 'static,
+        F: Future<Output = ()> + 'static,
+    {
+        let handler = Arc::new(handler);
+        let mut server = TcpServer {
+            local_addr: addr,
+            stops: vec![],
+            handles: vec![],
+        };
+        for index in 0..self.worker_threads() {
+            let (ready_tx, ready_rx) = oneshot::channel();
+            let (stop_tx, stop_rx) = oneshot::channel();
+            let handler = handler.clone();
+            let addr = server.local_addr;
+            let handle = self.dispatch_to(index, move || {
+                serve_on_worker(addr, handler, ready_tx, stop_rx)
+            })?;
+            server.stops.push(stop_tx);
+            server.handles.push(handle);
+            // The sender is dropped without sending only if the task panics.
+            match ready_rx.await {
+                Ok(Ok(local_addr)) => server.local_addr = local_addr,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => return Err(io::Error::other("the accept loop panicked")),
+            }
+        }
+        Ok(server)
+    }
+}
+
+// Bind the listener, report the address, and accept till stopped. Then wait
+// for the connections to complete.
+async fn serve_on_worker<H, F>(
+    addr: SocketAddr,
+    handler: Arc<H>,
+    ready: oneshot::Sender<io::Result<SocketAddr>>,
+    mut stop: oneshot::Receiver<()>,
+) -> io::Result<()>
+where
+    H: (Fn(TcpStream, SocketAddr) -> F) + 'static,
+    F: Future<Output = ()> + 'static,
+{
+    let listener = match TcpListener::bind_reuse_port(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            ready.send(Err(e)).ok();
+            return Ok(());
+        }
+    };
+    ready.send(listener.local_addr()).ok();
+
+    let connections = Rc::new(Connections {
+        active: Cell::new(0),
+        idle: Notify::new(),
+    });
+    let res = loop {
+        let accept = pin!(listener.accept());
+        // The server is also stopped if the sender is dropped.
+        let (stream, peer_addr) = match select(accept, &mut stop).await {
+            Either::Left((Ok(res), _)) => res,
+            Either::Left((Err(e), _)) if is_transient(&e) => continue,
+            Either::Left((Err(e), _)) => break Err(e),
+            Either::Right(_) => break Ok(()),
+        };
+        let guard = connections.enter();
+        let fut = handler(stream, peer_addr);
+        compio_runtime::spawn(async move {
+            fut.await;
+            drop(guard);
+        })
+        .detach();
+    };
+    drop(listener);
+    connections.wait_idle().await;
+    res
+}
+
+// The errors of a single connection, which should not stop the server.
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+    )
+}
+
+// Track the active connections of a worker.
+struct Connections {
+    active: Cell<usize>,
+    idle: Notify,
+}
+
+impl Connections {
+    fn enter(self: &Rc<Self>) -> ConnectionGuard {
+        self.active.set(self.active.get() + 1);
+        ConnectionGuard(self.clone())
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            if self.active.get() == 0 {
+                break;
+            }
+            notified.await;
+        }
+    }
+}
+
+// Leave the connections even if the handler panics.
+struct ConnectionGuard(Rc<Connections>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let active = self.0.active.get() - 1;
+        self.0.active.set(active);
+        if active == 0 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+/// A TCP server running on the workers of a [`Dispatcher`], returned by
+/// [`Dispatcher::serve_tcp`].
+///
+/// The server stops accepting if it is dropped, while the accepted
+/// connections are still served.
+#[must_use = "the server stops accepting if it is dropped"]
+#[derive(Debug)]
+pub struct TcpServer {
+    local_addr: SocketAddr,
+    stops: Vec<oneshot::Sender<()>>,
+    handles: Vec<JoinHandle<io::Result<()>>>,
+}
+
+impl TcpServer {
+    /// The address the listeners are bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting on all workers, and wait for the accepted connections
+    /// to complete. Returns the first error that stopped an accept loop.
+    pub async fn shutdown(self) -> io::Result<()> {
+        for stop in self.stops {
+            stop.send(()).ok();
+        }
+        let mut res = Ok(());
+        for handle in self.handles {
+            let r = handle
+                .await
+                .unwrap_or_else(|_| Err(io::Error::other("the accept loop panicked")));
+            if res.is_ok() {
+                res = r;
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    future::poll_fn,
+    pin::pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Poll, Waker},
+    time::Instant,
+};
+
+use compio_runtime::{channel::mpsc, time::timeout_at, ShutdownReport};
+use compio_sync::Semaphore;
+use futures_channel::oneshot;
+use futures_util::future::{select, Either};
+
+use crate::BoxClosure;
+
+/// The metrics of a worker thread of a [`Dispatcher`](crate::Dispatcher).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerMetrics {
+    /// The number of tasks in the queue, not received by the worker yet.
+    pub queued: usize,
+    /// The number of tasks dispatched to the worker and not completed,
+    /// including the queued ones.
+    pub in_flight: usize,
+    /// The capacity of the queue, or [`None`] if it is unbounded.
+    pub capacity: Option<usize>,
+    /// The total number of tasks dispatched to the worker.
+    pub dispatched: u64,
+    /// The total number of tasks completed by the worker.
+    pub completed: u64,
+}
+
+/// The outcome of a worker thread of a [`Dispatcher`](crate::Dispatcher),
+/// returned by [`Dispatcher::shutdown`](crate::Dispatcher::shutdown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WorkerReport {
+    /// If all tasks completed before the deadline.
+    pub graceful: bool,
+    /// The report of the runtime shutdown, which cancels the tasks not
+    /// completed before the deadline.
+    pub runtime: ShutdownReport,
+}
+
+impl WorkerReport {
+    pub(crate) fn new(graceful: bool, runtime: ShutdownReport) -> Self {
+        Self { graceful, runtime }
+    }
+}
+
+// The states of a worker, shared with the worker thread.
+pub(crate) struct WorkerState {
+    capacity: Option<usize>,
+    queued: AtomicUsize,
+    in_flight: AtomicUsize,
+    dispatched: AtomicU64,
+    completed: AtomicU64,
+    // The dispatchers waiting for the space of the queue.
+    space_waiters: Mutex<Vec<Waker>>,
+}
+
+impl WorkerState {
+    pub fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity,
+            queued: AtomicUsize::new(0),
+            in_flight: AtomicUsize::new(0),
+            dispatched: AtomicU64::new(0),
+            completed: AtomicU64::new(0),
+            space_waiters: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    // Reserve a slot in the queue. Returns false if the queue is full.
+    pub fn try_reserve(&self) -> bool {
+        let capacity = self.capacity.unwrap_or(usize::MAX);
+        let reserved = self
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                (queued < capacity).then_some(queued + 1)
+            })
+            .is_ok();
+        if reserved {
+            self.in_flight.fetch_add(1, Ordering::Relaxed);
+        }
+        reserved
+    }
+
+    // Wait until a slot in the queue is reserved.
+    pub async fn reserve(&self) {
+        poll_fn(|cx| {
+            if self.try_reserve() {
+                return Poll::Ready(());
+            }
+            {
+                let mut waiters = self.space_waiters.lock().unwrap();
+                if !waiters.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    waiters.push(cx.waker().clone());
+                }
+            }
+            // Check again in case the slot is released before the waker is
+            // registered.
+            if self.try_reserve() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    // Cancel a reservation if the task is not sent.
+    pub fn cancel(&self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.release();
+    }
+
+    pub fn on_dispatched(&self) {
+        self.dispatched.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Release a slot in the queue when the task is received by the worker.
+    fn release(&self) {
+        self.queued.fetch_sub(1, Ordering::AcqRel);
+        let wakers = std::mem::take(&mut *self.space_waiters.lock().unwrap());
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    fn on_completed(&self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn metrics(&self) -> WorkerMetrics {
+        WorkerMetrics {
+            queued: self.queued.load(Ordering::Relaxed),
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            capacity: self.capacity,
+            dispatched: self.dispatched.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub(crate) struct Worker {
+    pub sender: mpsc::Sender<BoxClosure<'static>>,
+    pub state: Arc<WorkerState>,
+    // Send the deadline to shut down.
+    pub stop: oneshot::Sender<Instant>,
+    pub report: oneshot::Receiver<WorkerReport>,
+}
+
+// Receive the dispatched closures and spawn them, till the dispatcher is
+// stopped and all tasks complete. If a deadline is received, it returns at
+// the deadline, and whether all tasks completed is returned.
+pub(crate) async fn run_worker(
+    receiver: mpsc::Receiver<BoxClosure<'static>>,
+    state: Arc<WorkerState>,
+    max_tasks: usize,
+    stop: oneshot::Receiver<Instant>,
+) -> bool {
+    let semaphore = Rc::new(Semaphore::new(max_tasks));
+    let work = pin!(work(receiver, state, &semaphore, max_tasks));
+    match select(work, stop).await {
+        Either::Left(((), _)) => true,
+        Either::Right((Ok(deadline), work)) => timeout_at(deadline, work).await.is_ok(),
+        // The dispatcher is dropped or joined.
+        Either::Right((Err(_), work)) => {
+            work.await;
+            true
+        }
+    }
+}
+
+async fn work(
+    mut receiver: mpsc::Receiver<BoxClosure<'static>>,
+    state: Arc<WorkerState>,
+    semaphore: &Rc<Semaphore>,
+    max_tasks: usize,
+) {
+    loop {
+        // The semaphore is never closed. The task is received only when it could
+        // run, so that the queue is not drained when the worker is busy, and the
+        // dispatchers are blocked.
+        let permit = semaphore.acquire().await.unwrap();
+        let Some(f) = receiver.recv().await else {
+            break;
+        };
+        state.release();
+        permit.forget();
+        let semaphore = semaphore.clone();
+        let state = state.clone();
+        compio_runtime::spawn(async move {
+            // The closure never panics, because the panic of the task is caught.
+            f().await;
+            state.on_completed();
+            semaphore.add_permits(1);
+        })
+        .detach();
+    }
+    // Wait for the running tasks.
+    drop(semaphore.acquire_many(max_tasks).await);
+}
@@ -81,6 +81,11 @@ mod driver_type {
             RecvMsg::CODE,
             SendMsg::CODE,
             AsyncCancel::CODE,
+            UnlinkAt::CODE,
+            MkDirAt::CODE,
+            RenameAt::CODE,
+            SymlinkAt::CODE,
+            LinkAt::CODE,
         ];
 
         Ok(())
@@ -132,6 +137,13 @@ impl Driver {
         }
     }
 
+    pub fn detach(&mut self, fd: RawFd) -> io::Result<()> {
+        match &mut self.fuse {
+            FuseDriver::Poll(driver) => driver.detach(fd),
+            FuseDriver::IoUring(driver) => driver.detach(fd),
+        }
+    }
+
     pub fn cancel(&mut self, user_data: usize, registry: &mut Slab<RawOp>) {
         match &mut self.fuse {
             FuseDriver::Poll(driver) => driver.cancel(user_data, registry),
@@ -157,6 +169,14 @@ impl Driver {
             FuseDriver::IoUring(driver) => driver.poll(timeout, entries, registry),
         }
     }
+
+    pub fn handle(&self) -> io::Result<NotifyHandle> {
+        let fuse = match &self.fuse {
+            FuseDriver::Poll(driver) => FuseNotifyHandle::Poll(driver.handle()?),
+            FuseDriver::IoUring(driver) => FuseNotifyHandle::IoUring(driver.handle()?),
+        };
+        Ok(NotifyHandle::from_fuse(fuse))
+    }
 }
 
 impl AsRawFd for Driver {
@@ -167,3 +187,27 @@ impl AsRawFd for Driver {
         }
     }
 }
+
+enum FuseNotifyHandle {
+    Poll(poll::NotifyHandle),
+    IoUring(iour::NotifyHandle),
+}
+
+/// A notify handle to the inner driver.
+pub struct NotifyHandle {
+    fuse: FuseNotifyHandle,
+}
+
+impl NotifyHandle {
+    fn from_fuse(fuse: FuseNotifyHandle) -> Self {
+        Self { fuse }
+    }
+
+    /// Notify the inner driver.
+    pub fn notify(&self) -> io::Result<()> {
+        match &self.fuse {
+            FuseNotifyHandle::Poll(handle) => handle.notify(),
+            FuseNotifyHandle::IoUring(handle) => handle.notify(),
+        }
+    }
+}
@@ -7,7 +7,8 @@ use std::{
         OwnedHandle, RawHandle,
     },
     pin::Pin,
-    ptr::NonNull,
+    ptr::{null_mut, NonNull},
+    sync::Arc,
     task::Poll,
     time::Duration,
 };
@@ -24,7 +25,10 @@ use windows_sys::Win32::{
     System::{
         Threading::INFINITE,
         WindowsProgramming::{FILE_SKIP_COMPLETION_PORT_ON_SUCCESS, FILE_SKIP_SET_EVENT_ON_HANDLE},
-        IO::{CreateIoCompletionPort, GetQueuedCompletionStatusEx, OVERLAPPED, OVERLAPPED_ENTRY},
+        IO::{
+            CreateIoCompletionPort, GetQueuedCompletionStatusEx, PostQueuedCompletionStatus,
+            OVERLAPPED, OVERLAPPED_ENTRY,
+        },
     },
 };
 
@@ -127,12 +131,13 @@ pub trait OpCode {
 
 /// Low-level driver of IOCP.
 pub(crate) struct Driver {
-    port: OwnedHandle,
+    port: Arc<OwnedHandle>,
     cancelled: HashSet<usize>,
 }
 
 impl Driver {
     const DEFAULT_CAPACITY: usize = 1024;
+    const NOTIFY: usize = usize::MAX;
 
     pub fn new(_entries: u32) -> io::Result<Self> {
         let mut data: WSADATA = unsafe { std::mem::zeroed() };
@@ -141,7 +146,7 @@ impl Driver {
         let port = syscall!(BOOL, CreateIoCompletionPort(INVALID_HANDLE_VALUE, 0, 0, 0))?;
         let port = unsafe { OwnedHandle::from_raw_handle(port as _) };
         Ok(Self {
-            port,
+            port: Arc::new(port),
             cancelled: HashSet::default(),
         })
     }
@@ -176,8 +181,11 @@ impl Driver {
 
     fn create_entry(&mut self, iocp_entry: OVERLAPPED_ENTRY) -> Option<Entry> {
         if iocp_entry.lpOverlapped.is_null() {
-            // This entry is posted by `post_driver_nop`.
+            // This entry is posted by `post_driver_nop` or `NotifyHandle::notify`.
             let user_data = iocp_entry.lpCompletionKey;
+            if user_data == Self::NOTIFY {
+                return None;
+            }
             let result = if self.cancelled.remove(&user_data) {
                 Err(io::Error::from_raw_os_error(ERROR_OPERATION_ABORTED as _))
             } else {
@@ -220,6 +228,13 @@ impl Driver {
         Ok(())
     }
 
+    pub fn detach(&mut self, _fd: RawFd) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "cannot detach a handle from the completion port",
+        ))
+    }
+
     pub fn cancel(&mut self, user_data: usize, registry: &mut Slab<RawOp>) {
         self.cancelled.insert(user_data);
         if let Some(op) = registry.get_mut(user_data) {
@@ -267,6 +282,10 @@ impl Driver {
 
         Ok(())
     }
+
+    pub fn handle(&self) -> io::Result<NotifyHandle> {
+        Ok(NotifyHandle::new(self.port.clone()))
+    }
 }
 
 impl AsRawFd for Driver {
@@ -281,6 +300,31 @@ impl Drop for Driver {
     }
 }
 
+/// A notify handle to the inner driver.
+pub struct NotifyHandle {
+    port: Arc<OwnedHandle>,
+}
+
+impl NotifyHandle {
+    fn new(port: Arc<OwnedHandle>) -> Self {
+        Self { port }
+    }
+
+    /// Notify the inner driver.
+    pub fn notify(&self) -> io::Result<()> {
+        syscall!(
+            BOOL,
+            PostQueuedCompletionStatus(
+                self.port.as_raw_handle() as _,
+                0,
+                Driver::NOTIFY,
+                null_mut()
+            )
+        )?;
+        Ok(())
+    }
+}
+
 /// The overlapped struct we actually used for IOCP.
 #[repr(C)]
 pub struct Overlapped<T: ?Sized> {
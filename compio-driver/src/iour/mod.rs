@@ -1,18 +1,20 @@
 #[cfg_attr(all(doc, docsrs), doc(cfg(all())))]
 pub use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
-use std::{collections::VecDeque, io, pin::Pin, task::Poll, time::Duration};
+use std::{
+    collections::VecDeque, io, os::fd::OwnedFd, pin::Pin, sync::Arc, task::Poll, time::Duration,
+};
 
 use io_uring::{
     cqueue,
-    opcode::AsyncCancel,
+    opcode::{AsyncCancel, Read},
     squeue,
-    types::{SubmitArgs, Timespec},
+    types::{Fd, SubmitArgs, Timespec},
     IoUring,
 };
 pub(crate) use libc::{sockaddr_storage, socklen_t};
 use slab::Slab;
 
-use crate::Entry;
+use crate::{syscall, Entry};
 
 pub(crate) mod op;
 pub(crate) use crate::unix::RawOp;
@@ -27,15 +29,20 @@ pub trait OpCode {
 pub(crate) struct Driver {
     inner: IoUring,
     squeue: VecDeque<squeue::Entry>,
+    notifier: Notifier,
+    notifier_registered: bool,
 }
 
 impl Driver {
     const CANCEL: u64 = u64::MAX;
+    const NOTIFY: u64 = u64::MAX - 1;
 
     pub fn new(entries: u32) -> io::Result<Self> {
         Ok(Self {
             inner: IoUring::new(entries)?,
             squeue: VecDeque::with_capacity(entries as usize),
+            notifier: Notifier::new()?,
+            notifier_registered: false,
         })
     }
 
@@ -54,6 +61,11 @@ impl Driver {
             self.inner.submit()
         };
         match res {
+            // If some entries are submitted, the syscall returns the submitted number instead of
+            // ETIME, even if it times out.
+            Ok(_) if wait && timeout.is_some() && self.inner.completion().is_empty() => {
+                Err(io::Error::from_raw_os_error(libc::ETIMEDOUT))
+            }
             Ok(_) => Ok(()),
             Err(e) => match e.raw_os_error() {
                 Some(libc::ETIME) => Err(io::Error::from_raw_os_error(libc::ETIMEDOUT)),
@@ -96,11 +108,17 @@ impl Driver {
     }
 
     fn poll_entries(&mut self, entries: &mut impl Extend<Entry>) {
+        let notifier_registered = &mut self.notifier_registered;
         let completed_entries =
             self.inner
                 .completion()
                 .filter_map(|entry| match entry.user_data() {
                     Self::CANCEL => None,
+                    Self::NOTIFY => {
+                        // The read of the eventfd completes; it should be pushed again.
+                        *notifier_registered = false;
+                        None
+                    }
                     _ => Some(create_entry(entry)),
                 });
         entries.extend(completed_entries);
@@ -110,6 +128,10 @@ impl Driver {
         Ok(())
     }
 
+    pub fn detach(&mut self, _fd: RawFd) -> io::Result<()> {
+        Ok(())
+    }
+
     pub fn cancel(&mut self, user_data: usize, _registry: &mut Slab<RawOp>) {
         self.squeue.push_back(
             AsyncCancel::new(user_data as _)
@@ -131,6 +153,11 @@ impl Driver {
         entries: &mut impl Extend<Entry>,
         _registry: &mut Slab<RawOp>,
     ) -> io::Result<()> {
+        if !self.notifier_registered {
+            self.squeue
+                .push_front(self.notifier.create_entry().user_data(Self::NOTIFY));
+            self.notifier_registered = true;
+        }
         // Anyway we need to submit once, no matter there are entries in squeue.
         loop {
             let ended = self.flush_submissions();
@@ -145,6 +172,20 @@ impl Driver {
         }
         Ok(())
     }
+
+    pub fn handle(&self) -> io::Result<NotifyHandle> {
+        Ok(self.notifier.handle())
+    }
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        if self.notifier_registered {
+            // The read of the notifier may be still in flight after the ring is
+            // dropped, and the kernel could write to the buffer. Leak it.
+            Box::leak(std::mem::take(&mut self.notifier.buffer));
+        }
+    }
 }
 
 impl AsRawFd for Driver {
@@ -173,3 +214,56 @@ fn timespec(duration: std::time::Duration) -> Timespec {
         .sec(duration.as_secs())
         .nsec(duration.subsec_nanos())
 }
+
+/// An eventfd, whose read operation is always pending in the ring, to wake
+/// up the driver from other threads.
+struct Notifier {
+    fd: Arc<OwnedFd>,
+    buffer: Box<u64>,
+}
+
+impl Notifier {
+    fn new() -> io::Result<Self> {
+        let fd = syscall!(libc::eventfd(0, libc::EFD_CLOEXEC))?;
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        Ok(Self {
+            fd: Arc::new(fd),
+            buffer: Box::new(0),
+        })
+    }
+
+    fn create_entry(&mut self) -> squeue::Entry {
+        Read::new(
+            Fd(self.fd.as_raw_fd()),
+            &mut *self.buffer as *mut u64 as _,
+            std::mem::size_of::<u64>() as _,
+        )
+        .build()
+    }
+
+    fn handle(&self) -> NotifyHandle {
+        NotifyHandle::new(self.fd.clone())
+    }
+}
+
+/// A notify handle to the inner driver.
+pub struct NotifyHandle {
+    fd: Arc<OwnedFd>,
+}
+
+impl NotifyHandle {
+    fn new(fd: Arc<OwnedFd>) -> Self {
+        Self { fd }
+    }
+
+    /// Notify the inner driver.
+    pub fn notify(&self) -> io::Result<()> {
+        let data = 1u64;
+        syscall!(libc::write(
+            self.fd.as_raw_fd(),
+            &data as *const _ as *const _,
+            std::mem::size_of::<u64>(),
+        ))?;
+        Ok(())
+    }
+}
@@ -329,3 +329,54 @@ impl<T: IoVectoredBuf> IntoInner for SendToVectored<T> {
         self.buffer
     }
 }
+
+impl OpCode for Unlink {
+    fn create_entry(self: Pin<&mut Self>) -> Entry {
+        opcode::UnlinkAt::new(Fd(libc::AT_FDCWD), self.path.as_ptr())
+            .flags(if self.dir { libc::AT_REMOVEDIR } else { 0 })
+            .build()
+    }
+}
+
+impl OpCode for CreateDir {
+    fn create_entry(self: Pin<&mut Self>) -> Entry {
+        opcode::MkDirAt::new(Fd(libc::AT_FDCWD), self.path.as_ptr())
+            .mode(self.mode)
+            .build()
+    }
+}
+
+impl OpCode for Rename {
+    fn create_entry(self: Pin<&mut Self>) -> Entry {
+        opcode::RenameAt::new(
+            Fd(libc::AT_FDCWD),
+            self.old_path.as_ptr(),
+            Fd(libc::AT_FDCWD),
+            self.new_path.as_ptr(),
+        )
+        .build()
+    }
+}
+
+impl OpCode for Symlink {
+    fn create_entry(self: Pin<&mut Self>) -> Entry {
+        opcode::SymlinkAt::new(
+            Fd(libc::AT_FDCWD),
+            self.source.as_ptr(),
+            self.target.as_ptr(),
+        )
+        .build()
+    }
+}
+
+impl OpCode for HardLink {
+    fn create_entry(self: Pin<&mut Self>) -> Entry {
+        opcode::LinkAt::new(
+            Fd(libc::AT_FDCWD),
+            self.source.as_ptr(),
+            Fd(libc::AT_FDCWD),
+            self.target.as_ptr(),
+        )
+        .build()
+    }
+}
@@ -184,6 +184,28 @@ impl Proactor {
         self.driver.attach(fd)
     }
 
+    /// Detach an fd from the driver, so that it could be attached to another
+    /// driver. There should be no pending operations on the fd.
+    ///
+    /// ## Platform specific
+    /// * IOCP: a handle could not be detached from the completion port, and it
+    ///   returns an error of [`io::ErrorKind::Unsupported`].
+    /// * io-uring: it will do nothing and return `Ok(())`.
+    /// * polling: it will deregister the fd from the driver, and return an
+    ///   error if there are operations waiting on it.
+    pub fn detach(&mut self, fd: RawFd) -> io::Result<()> {
+        self.driver.detach(fd)
+    }
+
+    /// Create a notify handle to interrupt the inner driver.
+    ///
+    /// The handle is [`Send`] and [`Sync`]. Calling [`NotifyHandle::notify`]
+    /// from any thread wakes up a blocking [`Proactor::poll`]. The poll may
+    /// return without any completed entries in this case.
+    pub fn handle(&self) -> io::Result<NotifyHandle> {
+        self.driver.handle()
+    }
+
     /// Cancel an operation with the pushed user-defined data.
     ///
     /// The cancellation is not reliable. The underlying operation may continue,
@@ -249,6 +271,16 @@ impl AsRawFd for Proactor {
     }
 }
 
+impl Drop for Proactor {
+    fn drop(&mut self) {
+        // The remaining operations may still be used by the kernel. Leak them
+        // instead of freeing the buffers.
+        std::mem::take(&mut self.ops)
+            .into_iter()
+            .for_each(|(_, op)| std::mem::forget(op));
+    }
+}
+
 /// Contains the operation and the user_data.
 pub struct Operation {
     op: RawOp,
@@ -13,7 +13,9 @@ pub use crate::sys::op::{
     SendVectored,
 };
 #[cfg(unix)]
-pub use crate::sys::op::{ReadVectoredAt, WriteVectoredAt};
+pub use crate::sys::op::{
+    CreateDir, HardLink, ReadVectoredAt, Rename, Symlink, Unlink, WriteVectoredAt,
+};
 use crate::sys::{sockaddr_storage, socklen_t, RawFd};
 
 /// Trait to update the buffer length inside the [`BufResult`].
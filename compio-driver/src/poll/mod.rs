@@ -6,6 +6,7 @@ use std::{
     num::NonZeroUsize,
     os::fd::BorrowedFd,
     pin::Pin,
+    sync::Arc,
     task::Poll,
     time::Duration,
 };
@@ -115,6 +116,10 @@ impl FdQueue {
         unreachable!("should not receive event when no interest")
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.read_queue.is_empty() && self.write_queue.is_empty()
+    }
+
     pub fn clear(&mut self) {
         self.read_queue.clear();
         self.write_queue.clear();
@@ -124,7 +129,7 @@ impl FdQueue {
 /// Low-level driver of polling.
 pub(crate) struct Driver {
     events: Events,
-    poll: Poller,
+    poll: Arc<Poller>,
     registry: HashMap<RawFd, FdQueue>,
     cancelled: HashSet<usize>,
 }
@@ -140,7 +145,7 @@ impl Driver {
 
         Ok(Self {
             events,
-            poll: Poller::new()?,
+            poll: Arc::new(Poller::new()?),
             registry: HashMap::new(),
             cancelled: HashSet::new(),
         })
@@ -184,6 +189,24 @@ impl Driver {
         Ok(())
     }
 
+    pub fn detach(&mut self, fd: RawFd) -> io::Result<()> {
+        // Normal files and directories are not registered.
+        let Some(queue) = self.registry.get(&fd) else {
+            return Ok(());
+        };
+        if !queue.is_empty() {
+            return Err(io::Error::other(
+                "cannot detach an fd with pending operations",
+            ));
+        }
+        self.registry.remove(&fd);
+        unsafe {
+            let fd = BorrowedFd::borrow_raw(fd);
+            self.poll.delete(fd)?;
+        }
+        Ok(())
+    }
+
     pub fn cancel(&mut self, user_data: usize, _registry: &mut Slab<RawOp>) {
         self.cancelled.insert(user_data);
     }
@@ -210,6 +233,8 @@ impl Driver {
         entries: &mut impl Extend<Entry>,
         registry: &mut Slab<RawOp>,
     ) -> io::Result<()> {
+        // The events are appended to the buffer, so the old ones should be cleared.
+        self.events.clear();
         self.poll.wait(&mut self.events, timeout)?;
         if self.events.is_empty() && timeout.is_some() {
             return Err(io::Error::from_raw_os_error(libc::ETIMEDOUT));
@@ -244,6 +269,10 @@ impl Driver {
         }
         Ok(())
     }
+
+    pub fn handle(&self) -> io::Result<NotifyHandle> {
+        Ok(NotifyHandle::new(self.poll.clone()))
+    }
 }
 
 impl AsRawFd for Driver {
@@ -269,3 +298,19 @@ fn entry_cancelled(user_data: usize) -> Entry {
         Err(io::Error::from_raw_os_error(libc::ETIMEDOUT)),
     )
 }
+
+/// A notify handle to the inner driver.
+pub struct NotifyHandle {
+    poll: Arc<Poller>,
+}
+
+impl NotifyHandle {
+    fn new(poll: Arc<Poller>) -> Self {
+        Self { poll }
+    }
+
+    /// Notify the inner driver.
+    pub fn notify(&self) -> io::Result<()> {
+        self.poll.notify()
+    }
+}
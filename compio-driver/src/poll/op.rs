@@ -461,3 +461,29 @@ impl<T: IoVectoredBuf> IntoInner for SendToVectored<T> {
         self.buffer
     }
 }
+
+// The path operations block the thread, and there is no readiness to wait for.
+// Let the caller run them on a blocking thread instead.
+macro_rules! unsupported_path_op {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl OpCode for $name {
+                fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+                    Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        concat!(stringify!($name), " is not supported by the polling driver"),
+                    ))
+                }
+
+                fn on_event(self: Pin<&mut Self>, _: &Event) -> Poll<io::Result<usize>> {
+                    unreachable!(concat!(
+                        stringify!($name),
+                        " operation should not be submitted to polling"
+                    ))
+                }
+            }
+        )*
+    };
+}
+
+unsupported_path_op!(Unlink, CreateDir, Rename, Symlink, HardLink);
@@ -1,3 +1,5 @@
+use std::ffi::CString;
+
 use compio_buf::{
     IntoInner, IoBuf, IoBufMut, IoSlice, IoSliceMut, IoVectoredBuf, IoVectoredBufMut,
 };
@@ -180,3 +182,77 @@ impl<T: IoVectoredBuf> IntoInner for SendVectored<T> {
         self.buffer
     }
 }
+
+/// Remove a file or an empty directory.
+///
+/// Only the io-uring driver supports the path operations. The other drivers
+/// fail with [`std::io::ErrorKind::Unsupported`], and the caller should fall
+/// back to a blocking thread.
+#[allow(dead_code)]
+pub struct Unlink {
+    pub(crate) path: CString,
+    pub(crate) dir: bool,
+}
+
+impl Unlink {
+    /// Create [`Unlink`].
+    pub fn new(path: CString, dir: bool) -> Self {
+        Self { path, dir }
+    }
+}
+
+/// Create a directory. See [`Unlink`] for the supported drivers.
+#[allow(dead_code)]
+pub struct CreateDir {
+    pub(crate) path: CString,
+    pub(crate) mode: libc::mode_t,
+}
+
+impl CreateDir {
+    /// Create [`CreateDir`].
+    pub fn new(path: CString, mode: libc::mode_t) -> Self {
+        Self { path, mode }
+    }
+}
+
+/// Rename a file or a directory. See [`Unlink`] for the supported drivers.
+#[allow(dead_code)]
+pub struct Rename {
+    pub(crate) old_path: CString,
+    pub(crate) new_path: CString,
+}
+
+impl Rename {
+    /// Create [`Rename`].
+    pub fn new(old_path: CString, new_path: CString) -> Self {
+        Self { old_path, new_path }
+    }
+}
+
+/// Create a symbolic link. See [`Unlink`] for the supported drivers.
+#[allow(dead_code)]
+pub struct Symlink {
+    pub(crate) source: CString,
+    pub(crate) target: CString,
+}
+
+impl Symlink {
+    /// Create [`Symlink`]. The link `target` points to `source`.
+    pub fn new(source: CString, target: CString) -> Self {
+        Self { source, target }
+    }
+}
+
+/// Create a hard link. See [`Unlink`] for the supported drivers.
+#[allow(dead_code)]
+pub struct HardLink {
+    pub(crate) source: CString,
+    pub(crate) target: CString,
+}
+
+impl HardLink {
+    /// Create [`HardLink`]. The link `target` points to `source`.
+    pub fn new(source: CString, target: CString) -> Self {
+        Self { source, target }
+    }
+}
@@ -25,45 +25,67 @@ use crate::OpenOptions;
 #[derive(Debug)]
 pub struct File {
     inner: std::fs::File,
+    // If the file is opened in the append mode.
+    append: bool,
+    // The alignment of direct IO to check the buffers against, in debug
+    // builds.
+    #[cfg(all(feature = "runtime", debug_assertions))]
+    direct_alignment: Option<usize>,
     #[cfg(feature = "runtime")]
     attacher: Attacher,
 }
 
-#[cfg(windows)]
-fn file_with_options(
-    path: impl AsRef<Path>,
-    mut options: std::fs::OpenOptions,
-) -> io::Result<std::fs::File> {
-    use std::os::windows::prelude::OpenOptionsExt;
-
-    use windows_sys::Win32::Storage::FileSystem::FILE_FLAG_OVERLAPPED;
-
-    options.custom_flags(FILE_FLAG_OVERLAPPED);
-    options.open(path)
-}
-
-#[cfg(not(windows))]
-fn file_with_options(
-    path: impl AsRef<Path>,
-    mut options: std::fs::OpenOptions,
-) -> io::Result<std::fs::File> {
-    use std::os::unix::prelude::OpenOptionsExt;
-
-    // Don't set nonblocking with epoll.
-    if cfg!(not(any(target_os = "linux", target_os = "android"))) {
-        options.custom_flags(libc::O_NONBLOCK);
-    }
-    options.open(path)
-}
-
 impl File {
-    pub(crate) fn with_options(path: impl AsRef<Path>, options: OpenOptions) -> io::Result<Self> {
-        let this = Self {
-            inner: file_with_options(path, options.0)?,
+    pub(crate) fn from_std(inner: std::fs::File, append: bool) -> Self {
+        Self {
+            inner,
+            append,
+            #[cfg(all(feature = "runtime", debug_assertions))]
+            direct_alignment: None,
             #[cfg(feature = "runtime")]
             attacher: Attacher::new(),
+        }
+    }
+
+    // Check the buffers of the IO operations, if the alignment could be
+    // queried.
+    #[cfg(all(feature = "runtime", debug_assertions))]
+    pub(crate) fn check_direct_io(mut self) -> Self {
+        self.direct_alignment = self.direct_io_alignment().ok();
+        self
+    }
+
+    // Reject the misaligned buffers of direct IO with a clear error, instead of
+    // an obscure `EINVAL` from the kernel.
+    #[cfg(all(feature = "runtime", debug_assertions))]
+    fn check_aligned(
+        &self,
+        bufs: impl IntoIterator<Item = (*const u8, usize)>,
+        pos: u64,
+    ) -> io::Result<()> {
+        let Some(align) = self.direct_alignment else {
+            return Ok(());
         };
-        Ok(this)
+        // The writes go to the end of the file in the append mode, where the
+        // position is a placeholder, e.g., `u64::MAX` on Windows.
+        if !self.append && !pos.is_multiple_of(align as u64) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("misaligned position {pos} for direct IO, expected a multiple of {align}"),
+            ));
+        }
+        for (ptr, len) in bufs {
+            if !(ptr as usize).is_multiple_of(align) || !len.is_multiple_of(align) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "misaligned buffer at {ptr:p} with length {len} for direct IO, expected \
+                         multiples of {align}"
+                    ),
+                ));
+            }
+        }
+        Ok(())
     }
 
     /// Attempts to open a file in read-only mode.
@@ -97,6 +119,9 @@ impl File {
             #[cfg(feature = "runtime")]
             attacher: self.attacher.try_clone(&inner)?,
             inner,
+            append: self.append,
+            #[cfg(all(feature = "runtime", debug_assertions))]
+            direct_alignment: self.direct_alignment,
         })
     }
 
@@ -105,6 +130,82 @@ impl File {
         self.inner.metadata()
     }
 
+    /// Queries the alignment of direct IO on this file, in bytes.
+    ///
+    /// The addresses and lengths of the buffers, and the positions of the IO
+    /// operations on a file opened with [`OpenOptions::direct`] should be
+    /// multiples of the alignment. The `AlignedBuf` of `compio-buf` could be
+    /// used to allocate such buffers. In debug builds, the misaligned
+    /// operations fail with [`io::ErrorKind::InvalidInput`] before submitted.
+    ///
+    /// On Linux, it is queried with `statx` and `STATX_DIOALIGN`, and
+    /// [`io::ErrorKind::Unsupported`] is returned if the file doesn't support
+    /// direct IO. Before Linux 6.1, it falls back to the logical block size of
+    /// a block device, or the block size of the filesystem. On Windows, it's
+    /// the sector size of the volume. On the other platforms, it's the block
+    /// size of the filesystem.
+    pub fn direct_io_alignment(&self) -> io::Result<usize> {
+        direct_io_alignment(&self.inner)
+    }
+
+    /// Writes a buffer to the end of the file, and returns the number of bytes
+    /// written.
+    ///
+    /// The file must be opened in the append mode with
+    /// [`OpenOptions::append`], otherwise an error with
+    /// [`io::ErrorKind::InvalidInput`] is returned. The write is atomic on
+    /// Linux and Windows, where the kernel appends the data, while on the other
+    /// platforms the current length of the file is used as the position.
+    ///
+    /// ```
+    /// use compio_fs::OpenOptions;
+    ///
+    /// # compio_runtime::block_on(async {
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let path = dir.path().join("append.txt");
+    /// compio_fs::write(&path, "hello").await.unwrap();
+    /// let file = OpenOptions::new().append(true).open(&path).unwrap();
+    /// file.append(" world").await.unwrap();
+    /// assert_eq!(compio_fs::read(&path).await.unwrap(), b"hello world");
+    /// # })
+    /// ```
+    #[cfg(feature = "runtime")]
+    pub async fn append<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        if !self.append {
+            return BufResult(
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the file is not opened in the append mode",
+                )),
+                buffer,
+            );
+        }
+        // The position is ignored in the append mode.
+        (&*self).write_at(buffer, 0).await
+    }
+
+    // The position passed to the driver for the writes in the append mode.
+    #[cfg(all(feature = "runtime", any(target_os = "linux", target_os = "android")))]
+    fn append_pos(&self) -> io::Result<u64> {
+        // The offset is ignored by `pwrite` and io-uring with `O_APPEND`.
+        Ok(0)
+    }
+
+    #[cfg(all(feature = "runtime", windows))]
+    fn append_pos(&self) -> io::Result<u64> {
+        // Both `Offset` and `OffsetHigh` are `0xFFFFFFFF`.
+        Ok(u64::MAX)
+    }
+
+    #[cfg(all(
+        feature = "runtime",
+        unix,
+        not(any(target_os = "linux", target_os = "android"))
+    ))]
+    fn append_pos(&self) -> io::Result<u64> {
+        Ok(self.inner.metadata()?.len())
+    }
+
     #[cfg(feature = "runtime")]
     async fn sync_impl(&self, datasync: bool) -> io::Result<()> {
         self.attach()?;
@@ -143,6 +244,11 @@ impl File {
 impl AsyncReadAt for File {
     async fn read_at<T: IoBufMut>(&self, buffer: T, pos: u64) -> BufResult<usize, T> {
         let ((), buffer) = buf_try!(self.attach(), buffer);
+        #[cfg(debug_assertions)]
+        let ((), buffer) = {
+            let res = self.check_aligned([(buffer.as_buf_ptr(), buffer.buf_capacity())], pos);
+            buf_try!(res, buffer)
+        };
         let op = ReadAt::new(self.as_raw_fd(), pos, buffer);
         submit(op).await.into_inner().map_advanced()
     }
@@ -154,6 +260,17 @@ impl AsyncReadAt for File {
         pos: u64,
     ) -> BufResult<usize, T> {
         let ((), buffer) = buf_try!(self.attach(), buffer);
+        #[cfg(debug_assertions)]
+        let ((), buffer) = {
+            let mut buffer = buffer;
+            let res = self.check_aligned(
+                buffer
+                    .as_dyn_mut_bufs()
+                    .map(|buf| (buf.as_buf_ptr(), buf.buf_capacity())),
+                pos,
+            );
+            buf_try!(res, buffer)
+        };
         let op = ReadVectoredAt::new(self.as_raw_fd(), pos, buffer);
         submit(op).await.into_inner().map_advanced()
     }
@@ -179,8 +296,20 @@ impl AsyncWriteAt for File {
 
 #[cfg(feature = "runtime")]
 impl AsyncWriteAt for &File {
+    /// Writes at `pos`, or at the end of the file if it's opened in the append
+    /// mode, where `pos` is ignored.
     async fn write_at<T: IoBuf>(&mut self, buffer: T, pos: u64) -> BufResult<usize, T> {
         let ((), buffer) = buf_try!(self.attach(), buffer);
+        let (pos, buffer) = if self.append {
+            buf_try!(self.append_pos(), buffer)
+        } else {
+            (pos, buffer)
+        };
+        #[cfg(debug_assertions)]
+        let ((), buffer) = {
+            let res = self.check_aligned([(buffer.as_buf_ptr(), buffer.buf_len())], pos);
+            buf_try!(res, buffer)
+        };
         let op = WriteAt::new(self.as_raw_fd(), pos, buffer);
         submit(op).await.into_inner()
     }
@@ -192,11 +321,102 @@ impl AsyncWriteAt for &File {
         pos: u64,
     ) -> BufResult<usize, T> {
         let ((), buffer) = buf_try!(self.attach(), buffer);
+        let (pos, buffer) = if self.append {
+            buf_try!(self.append_pos(), buffer)
+        } else {
+            (pos, buffer)
+        };
+        #[cfg(debug_assertions)]
+        let ((), buffer) = {
+            let res = self.check_aligned(
+                buffer
+                    .as_dyn_bufs()
+                    .map(|buf| (buf.as_buf_ptr(), buf.buf_len())),
+                pos,
+            );
+            buf_try!(res, buffer)
+        };
         let op = WriteVectoredAt::new(self.as_raw_fd(), pos, buffer);
         submit(op).await.into_inner()
     }
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn direct_io_alignment(file: &std::fs::File) -> io::Result<usize> {
+    use std::{
+        mem::MaybeUninit,
+        os::unix::fs::{FileTypeExt, MetadataExt},
+    };
+
+    let mut stat = MaybeUninit::<libc::statx>::zeroed();
+    let res = unsafe {
+        libc::statx(
+            file.as_raw_fd(),
+            c"".as_ptr(),
+            libc::AT_EMPTY_PATH,
+            libc::STATX_DIOALIGN,
+            stat.as_mut_ptr(),
+        )
+    };
+    if res == 0 {
+        let stat = unsafe { stat.assume_init() };
+        if stat.stx_mask & libc::STATX_DIOALIGN != 0 {
+            let align = stat.stx_dio_mem_align.max(stat.stx_dio_offset_align);
+            if align == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "direct IO is not supported by the file",
+                ));
+            }
+            return Ok(align as usize);
+        }
+    }
+    // `STATX_DIOALIGN` is not supported.
+    let metadata = file.metadata()?;
+    if metadata.file_type().is_block_device() {
+        let mut size: libc::c_int = 0;
+        if unsafe { libc::ioctl(file.as_raw_fd(), libc::BLKSSZGET as _, &mut size) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(size as usize)
+    } else {
+        Ok(metadata.blksize() as usize)
+    }
+}
+
+#[cfg(windows)]
+fn direct_io_alignment(file: &std::fs::File) -> io::Result<usize> {
+    use std::mem::MaybeUninit;
+
+    use windows_sys::Win32::Storage::FileSystem::{
+        FileStorageInfo, GetFileInformationByHandleEx, FILE_STORAGE_INFO,
+    };
+
+    let mut info = MaybeUninit::<FILE_STORAGE_INFO>::zeroed();
+    let res = unsafe {
+        GetFileInformationByHandleEx(
+            file.as_raw_fd() as _,
+            FileStorageInfo,
+            info.as_mut_ptr().cast(),
+            std::mem::size_of::<FILE_STORAGE_INFO>() as _,
+        )
+    };
+    if res == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let info = unsafe { info.assume_init() };
+    Ok(info
+        .LogicalBytesPerSector
+        .max(info.PhysicalBytesPerSectorForPerformance) as usize)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn direct_io_alignment(file: &std::fs::File) -> io::Result<usize> {
+    use std::os::unix::fs::MetadataExt;
+
+    Ok(file.metadata()?.blksize() as usize)
+}
+
 impl AsRawFd for File {
     fn as_raw_fd(&self) -> RawFd {
         self.inner.as_raw_fd()
@@ -207,6 +427,9 @@ impl FromRawFd for File {
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
         Self {
             inner: FromRawFd::from_raw_fd(fd),
+            append: false,
+            #[cfg(all(feature = "runtime", debug_assertions))]
+            direct_alignment: None,
             #[cfg(feature = "runtime")]
             attacher: compio_runtime::Attacher::new(),
         }
@@ -228,4 +451,12 @@ impl Attachable for File {
     fn is_attached(&self) -> bool {
         self.attacher.is_attached()
     }
+
+    fn detach(&mut self) -> io::Result<()> {
+        self.attacher.detach(&self.inner)
+    }
 }
+
+// Safety: the file is thread safe apart from the attacher.
+#[cfg(feature = "runtime")]
+unsafe impl compio_runtime::Detachable for File {}
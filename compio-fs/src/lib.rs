@@ -9,6 +9,16 @@ pub use file::*;
 mod open_options;
 pub use open_options::*;
 
+#[cfg(feature = "runtime")]
+mod read_dir;
+#[cfg(feature = "runtime")]
+pub use read_dir::*;
+
+#[cfg(feature = "runtime")]
+mod utils;
+#[cfg(feature = "runtime")]
+pub use utils::*;
+
 #[cfg(windows)]
 pub mod named_pipe;
 
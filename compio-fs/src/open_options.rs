@@ -39,14 +39,29 @@ use crate::File;
 ///     .unwrap();
 /// ```
 #[derive(Debug, Clone)]
-pub struct OpenOptions(pub(crate) StdOpenOptions);
+pub struct OpenOptions {
+    options: StdOpenOptions,
+    append: bool,
+    direct: bool,
+    sync: bool,
+    #[cfg(unix)]
+    custom_flags: i32,
+    #[cfg(windows)]
+    custom_flags: u32,
+}
 
 impl OpenOptions {
     /// Creates a blank new set of options ready for configuration.
     #[allow(clippy::new_without_default)]
     #[must_use]
     pub fn new() -> Self {
-        Self(StdOpenOptions::new())
+        Self {
+            options: StdOpenOptions::new(),
+            append: false,
+            direct: false,
+            sync: false,
+            custom_flags: 0,
+        }
     }
 
     /// Sets the option for read access.
@@ -54,7 +69,7 @@ impl OpenOptions {
     /// This option, when true, will indicate that the file should be
     /// `read`-able if opened.
     pub fn read(mut self, read: bool) -> Self {
-        self.0.read(read);
+        self.options.read(read);
         self
     }
 
@@ -63,7 +78,26 @@ impl OpenOptions {
     /// This option, when true, will indicate that the file should be
     /// `write`-able if opened.
     pub fn write(mut self, write: bool) -> Self {
-        self.0.write(write);
+        self.options.write(write);
+        self
+    }
+
+    /// Sets the option for the append mode.
+    ///
+    /// This option, when true, means that writes will append to a file instead
+    /// of overwriting previous contents. Note that setting
+    /// `.write(true).append(true)` has the same effect as setting only
+    /// `.append(true)`.
+    ///
+    /// The positional write operations always carry an offset, which is
+    /// ignored for a file opened in the append mode: [`File::append`] and the
+    /// [`AsyncWriteAt`] methods all write to the end of the file, no matter
+    /// what position is passed.
+    ///
+    /// [`AsyncWriteAt`]: compio_io::AsyncWriteAt
+    pub fn append(mut self, append: bool) -> Self {
+        self.options.append(append);
+        self.append = append;
         self
     }
 
@@ -74,7 +108,7 @@ impl OpenOptions {
     ///
     /// The file must be opened with write access for truncate to work.
     pub fn truncate(mut self, truncate: bool) -> Self {
-        self.0.truncate(truncate);
+        self.options.truncate(truncate);
         self
     }
 
@@ -83,7 +117,7 @@ impl OpenOptions {
     /// In order for the file to be created, [`OpenOptions::write`] access must
     /// be used.
     pub fn create(mut self, create: bool) -> Self {
-        self.0.create(create);
+        self.options.create(create);
         self
     }
 
@@ -106,7 +140,69 @@ impl OpenOptions {
     /// [`.create()`]: OpenOptions::create
     /// [`.truncate()`]: OpenOptions::truncate
     pub fn create_new(mut self, create_new: bool) -> Self {
-        self.0.create_new(create_new);
+        self.options.create_new(create_new);
+        self
+    }
+
+    /// Sets the option to bypass the page cache of the OS, with `O_DIRECT` on
+    /// Linux, `F_NOCACHE` on macOS and `FILE_FLAG_NO_BUFFERING` on Windows.
+    ///
+    /// The buffers, offsets and lengths of the IO operations on such a file
+    /// usually need to be aligned to the logical block size of the device,
+    /// otherwise the operations fail with [`io::ErrorKind::InvalidInput`]. If
+    /// the platform doesn't support it, [`OpenOptions::open`] fails with
+    /// [`io::ErrorKind::Unsupported`].
+    pub fn direct(mut self, direct: bool) -> Self {
+        self.direct = direct;
+        self
+    }
+
+    /// Sets the option to synchronize the data of each write to the device,
+    /// with `O_DSYNC` on Unix and `FILE_FLAG_WRITE_THROUGH` on Windows.
+    ///
+    /// The writes complete only when the data, and the metadata needed to
+    /// retrieve it, reach the device, as if [`File::sync_data`] is called
+    /// after each write.
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Pass custom flags to the `flags` argument of `open`, or the
+    /// `dwFlagsAndAttributes` argument of `CreateFileW` on Windows.
+    ///
+    /// The flags are combined with the ones needed by the runtime and the
+    /// other options, instead of overriding them. The access mode bits are
+    /// masked out on Unix.
+    #[cfg(unix)]
+    pub fn custom_flags(mut self, flags: i32) -> Self {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Pass custom flags to the `flags` argument of `open`, or the
+    /// `dwFlagsAndAttributes` argument of `CreateFileW` on Windows.
+    ///
+    /// The flags are combined with the ones needed by the runtime and the
+    /// other options, instead of overriding them.
+    #[cfg(windows)]
+    pub fn custom_flags(mut self, flags: u32) -> Self {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Sets the mode bits that a new file will be created with.
+    ///
+    /// If a new file is created as part of an [`OpenOptions::open`] call then
+    /// this specified `mode` will be used as the permission bits for the new
+    /// file. If no `mode` is set, the default of `0o666` will be used. The
+    /// operating system masks out bits with the system's `umask`, to produce
+    /// the final permissions.
+    #[cfg(unix)]
+    pub fn mode(mut self, mode: u32) -> Self {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        self.options.mode(mode);
         self
     }
 
@@ -114,6 +210,115 @@ impl OpenOptions {
     ///
     /// See [`std::fs::OpenOptions::open`].
     pub fn open(self, path: impl AsRef<Path>) -> io::Result<File> {
-        File::with_options(path, self)
+        let file = self.std_options()?.open(path)?;
+        self.wrap(file)
+    }
+
+    // Open the file on the blocking thread pool, so that the path lookup doesn't
+    // block the runtime thread.
+    #[cfg(feature = "runtime")]
+    pub(crate) async fn open_async(self, path: impl AsRef<Path>) -> io::Result<File> {
+        let options = self.std_options()?;
+        let path = path.as_ref().to_path_buf();
+        let file = compio_runtime::spawn_blocking(move || options.open(path)).await?;
+        self.wrap(file)
+    }
+
+    // Apply the options that std doesn't support to the opened file.
+    fn wrap(&self, file: std::fs::File) -> io::Result<File> {
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        if self.direct {
+            use std::os::fd::AsRawFd;
+
+            if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        let file = File::from_std(file, self.append);
+        #[cfg(all(feature = "runtime", debug_assertions))]
+        let file = if self.direct {
+            file.check_direct_io()
+        } else {
+            file
+        };
+        Ok(file)
     }
+
+    // Set the flags at once, because `custom_flags` of std overrides the
+    // previous value.
+    #[cfg(windows)]
+    fn std_options(&self) -> io::Result<StdOpenOptions> {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        use windows_sys::Win32::Storage::FileSystem::{
+            FILE_FLAG_NO_BUFFERING, FILE_FLAG_OVERLAPPED, FILE_FLAG_WRITE_THROUGH,
+        };
+
+        let mut flags = self.custom_flags | FILE_FLAG_OVERLAPPED;
+        if self.direct {
+            flags |= FILE_FLAG_NO_BUFFERING;
+        }
+        if self.sync {
+            flags |= FILE_FLAG_WRITE_THROUGH;
+        }
+        let mut options = self.options.clone();
+        options.custom_flags(flags);
+        Ok(options)
+    }
+
+    #[cfg(unix)]
+    fn std_options(&self) -> io::Result<StdOpenOptions> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut flags = self.custom_flags;
+        // Don't set nonblocking with epoll.
+        if cfg!(not(any(target_os = "linux", target_os = "android"))) {
+            flags |= libc::O_NONBLOCK;
+        }
+        if self.direct {
+            flags |= direct_flag()?;
+        }
+        if self.sync {
+            flags |= libc::O_DSYNC;
+        }
+        let mut options = self.options.clone();
+        options.custom_flags(flags);
+        Ok(options)
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "dragonfly"
+))]
+fn direct_flag() -> io::Result<i32> {
+    Ok(libc::O_DIRECT)
+}
+
+// `F_NOCACHE` is set after the file is opened.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn direct_flag() -> io::Result<i32> {
+    Ok(0)
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "dragonfly",
+        target_os = "macos",
+        target_os = "ios"
+    ))
+))]
+fn direct_flag() -> io::Result<i32> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "direct IO is not supported on this platform",
+    ))
 }
@@ -0,0 +1,282 @@
+use std::{
+    collections::VecDeque,
+    ffi::OsString,
+    fmt,
+    fs::{FileType, Metadata},
+    future::{poll_fn, Future},
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use compio_runtime::spawn_blocking;
+use futures_util::{
+    stream::{FuturesUnordered, SelectAll},
+    Stream, StreamExt,
+};
+
+// The number of entries read in one blocking call.
+const BATCH_SIZE: usize = 32;
+
+type Batch = (Option<std::fs::ReadDir>, VecDeque<io::Result<DirEntry>>);
+
+/// Returns a stream over the entries within a directory.
+///
+/// The directory is opened and read on the blocking thread pool, in batches of
+/// entries, so that the runtime thread is never blocked by `getdents64` or
+/// its equivalents. The file type of each entry is resolved in the same batch,
+/// while the metadata is only queried on demand by [`DirEntry::metadata`].
+///
+/// The order of the entries is not guaranteed.
+///
+/// ```
+/// # compio_runtime::block_on(async {
+/// let dir = tempfile::tempdir().unwrap();
+/// compio_fs::write(dir.path().join("hello.txt"), "hello")
+///     .await
+///     .unwrap();
+///
+/// let mut entries = compio_fs::read_dir(dir.path()).await.unwrap();
+/// while let Some(entry) = entries.next_entry().await.unwrap() {
+///     assert_eq!(entry.file_name(), "hello.txt");
+///     assert!(entry.file_type().is_file());
+///     assert_eq!(entry.metadata().await.unwrap().len(), 5);
+/// }
+/// # })
+/// ```
+pub async fn read_dir(path: impl AsRef<Path>) -> io::Result<ReadDir> {
+    let path = path.as_ref().to_path_buf();
+    let inner = spawn_blocking(move || std::fs::read_dir(path)).await?;
+    Ok(ReadDir::new(inner))
+}
+
+/// Stream of the entries in a directory, returned by [`read_dir`].
+///
+/// It yields [`io::Result`]`<`[`DirEntry`]`>`, and an error of an entry
+/// doesn't stop the stream.
+pub struct ReadDir {
+    // `None` if the directory is exhausted, or a batch is being read.
+    inner: Option<std::fs::ReadDir>,
+    entries: VecDeque<io::Result<DirEntry>>,
+    batch: Option<Pin<Box<dyn Future<Output = Batch>>>>,
+}
+
+impl ReadDir {
+    fn new(inner: std::fs::ReadDir) -> Self {
+        Self {
+            inner: Some(inner),
+            entries: VecDeque::new(),
+            batch: None,
+        }
+    }
+
+    /// Returns the next entry in the directory, or [`None`] if there are no
+    /// more entries.
+    pub async fn next_entry(&mut self) -> io::Result<Option<DirEntry>> {
+        poll_fn(|cx| self.poll_next_unpin(cx)).await.transpose()
+    }
+}
+
+// Read a batch of entries, and return the iterator back if it's not exhausted.
+fn read_batch(mut inner: std::fs::ReadDir) -> Batch {
+    let entries = inner
+        .by_ref()
+        .take(BATCH_SIZE)
+        .map(|entry| entry.and_then(DirEntry::new))
+        .collect::<VecDeque<_>>();
+    let inner = (entries.len() == BATCH_SIZE).then_some(inner);
+    (inner, entries)
+}
+
+impl Stream for ReadDir {
+    type Item = io::Result<DirEntry>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(entry) = self.entries.pop_front() {
+                return Poll::Ready(Some(entry));
+            }
+            if let Some(batch) = &mut self.batch {
+                let (inner, entries) = ready!(batch.as_mut().poll(cx));
+                self.batch = None;
+                self.inner = inner;
+                self.entries = entries;
+                continue;
+            }
+            match self.inner.take() {
+                Some(inner) => {
+                    self.batch = Some(Box::pin(spawn_blocking(move || read_batch(inner))))
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ReadDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadDir")
+            .field("inner", &self.inner)
+            .field("entries", &self.entries)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Entries returned by the [`ReadDir`] stream.
+///
+/// An instance of `DirEntry` represents an entry inside of a directory on the
+/// filesystem. Each entry can be inspected via methods to learn about the full
+/// path or possibly other metadata.
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    inner: Arc<std::fs::DirEntry>,
+    file_type: FileType,
+}
+
+impl DirEntry {
+    fn new(inner: std::fs::DirEntry) -> io::Result<Self> {
+        let file_type = inner.file_type()?;
+        Ok(Self {
+            inner: Arc::new(inner),
+            file_type,
+        })
+    }
+
+    /// Returns the full path to the file that this entry represents.
+    ///
+    /// The full path is created by joining the original path to [`read_dir`]
+    /// with the filename of this entry.
+    pub fn path(&self) -> PathBuf {
+        self.inner.path()
+    }
+
+    /// Returns the bare file name of this directory entry without any other
+    /// leading path component.
+    pub fn file_name(&self) -> OsString {
+        self.inner.file_name()
+    }
+
+    /// Returns the file type for the file that this entry points at.
+    ///
+    /// The file type is resolved when the entry is read, and this function
+    /// will not traverse symlinks if this entry points at a symlink.
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns the metadata for the file that this entry points at.
+    ///
+    /// The metadata is queried on the blocking thread pool, and this function
+    /// will not traverse symlinks if this entry points at a symlink.
+    pub async fn metadata(&self) -> io::Result<Metadata> {
+        let inner = self.inner.clone();
+        spawn_blocking(move || inner.metadata()).await
+    }
+}
+
+/// Returns a stream over all entries within a directory, recursively.
+///
+/// The entries of the subdirectories are yielded after the subdirectory
+/// itself, while `path` itself is not yielded. At most `max_concurrent`
+/// directories are read at the same time, and the order of the entries is not
+/// guaranteed. Symbolic links are yielded but not followed. An error of an
+/// entry or a subdirectory doesn't stop the walk.
+///
+/// If `max_concurrent` is 0, it is treated as 1.
+///
+/// ```
+/// use futures_util::TryStreamExt;
+///
+/// # compio_runtime::block_on(async {
+/// let dir = tempfile::tempdir().unwrap();
+/// compio_fs::create_dir_all(dir.path().join("a/b"))
+///     .await
+///     .unwrap();
+/// compio_fs::write(dir.path().join("a/b/hello.txt"), "hello")
+///     .await
+///     .unwrap();
+///
+/// let entries = compio_fs::walk_dir(dir.path(), 4)
+///     .try_collect::<Vec<_>>()
+///     .await
+///     .unwrap();
+/// assert_eq!(entries.len(), 3);
+/// # })
+/// ```
+pub fn walk_dir(path: impl AsRef<Path>, max_concurrent: usize) -> WalkDir {
+    WalkDir {
+        max_concurrent: max_concurrent.max(1),
+        queue: VecDeque::from([path.as_ref().to_path_buf()]),
+        opening: FuturesUnordered::new(),
+        reading: SelectAll::new(),
+    }
+}
+
+/// Stream of the entries in a directory tree, returned by [`walk_dir`].
+pub struct WalkDir {
+    max_concurrent: usize,
+    // The directories waiting to be read.
+    queue: VecDeque<PathBuf>,
+    opening: FuturesUnordered<Pin<Box<dyn Future<Output = io::Result<ReadDir>>>>>,
+    reading: SelectAll<ReadDir>,
+}
+
+impl WalkDir {
+    fn active(&self) -> usize {
+        self.opening.len() + self.reading.len()
+    }
+}
+
+impl Stream for WalkDir {
+    type Item = io::Result<DirEntry>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            while self.active() < self.max_concurrent {
+                let Some(path) = self.queue.pop_front() else {
+                    break;
+                };
+                self.opening.push(Box::pin(read_dir(path)));
+            }
+            match self.opening.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(dir))) => {
+                    self.reading.push(dir);
+                    continue;
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) | Poll::Pending => {}
+            }
+            match self.reading.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(entry))) => {
+                    if entry.file_type().is_dir() {
+                        self.queue.push_back(entry.path());
+                    }
+                    return Poll::Ready(Some(Ok(entry)));
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) | Poll::Pending => {}
+            }
+            // Some directories may be exhausted, and leave room for the queued
+            // ones.
+            if self.active() < self.max_concurrent && !self.queue.is_empty() {
+                continue;
+            }
+            if self.active() == 0 {
+                return Poll::Ready(None);
+            }
+            return Poll::Pending;
+        }
+    }
+}
+
+impl fmt::Debug for WalkDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkDir")
+            .field("max_concurrent", &self.max_concurrent)
+            .field("queue", &self.queue)
+            .field("active", &self.active())
+            .finish_non_exhaustive()
+    }
+}
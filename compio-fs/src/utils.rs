@@ -0,0 +1,294 @@
+#[cfg(unix)]
+use std::ffi::CString;
+use std::{
+    fs::{Metadata, Permissions},
+    io,
+    path::{Path, PathBuf},
+};
+
+use compio_buf::{buf_try, BufResult, IoBuf};
+#[cfg(unix)]
+use compio_driver::{
+    op::{CreateDir, HardLink, Rename, Symlink, Unlink},
+    OpCode,
+};
+use compio_io::{AsyncReadAtExt, AsyncWriteAtExt};
+use compio_runtime::spawn_blocking;
+
+use crate::OpenOptions;
+
+// Run a path operation on the blocking thread pool.
+async fn asyncify_path<T: Send + 'static>(
+    path: impl AsRef<Path>,
+    f: impl (FnOnce(PathBuf) -> io::Result<T>) + Send + 'static,
+) -> io::Result<T> {
+    let path = path.as_ref().to_path_buf();
+    spawn_blocking(move || f(path)).await
+}
+
+// Run an operation on two paths on the blocking thread pool.
+async fn asyncify_paths<T: Send + 'static>(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+    f: impl (FnOnce(PathBuf, PathBuf) -> io::Result<T>) + Send + 'static,
+) -> io::Result<T> {
+    let from = from.as_ref().to_path_buf();
+    let to = to.as_ref().to_path_buf();
+    spawn_blocking(move || f(from, to)).await
+}
+
+#[cfg(unix)]
+fn path_string(path: &Path) -> io::Result<CString> {
+    use std::os::unix::ffi::OsStrExt;
+
+    CString::new(path.as_os_str().as_bytes()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "file name contained an unexpected NUL byte",
+        )
+    })
+}
+
+// Run a path operation with the driver, or on the blocking thread pool if the
+// driver doesn't support it.
+#[cfg(unix)]
+async fn submit_path<T: OpCode + 'static>(
+    path: impl AsRef<Path>,
+    op: impl FnOnce(CString) -> T,
+    f: impl (FnOnce(PathBuf) -> io::Result<()>) + Send + 'static,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    match compio_runtime::submit(op(path_string(path)?)).await.0 {
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => asyncify_path(path, f).await,
+        res => res.map(|_| ()),
+    }
+}
+
+// Run an operation on two paths with the driver, or on the blocking thread pool
+// if the driver doesn't support it.
+#[cfg(unix)]
+async fn submit_paths<T: OpCode + 'static>(
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+    op: impl FnOnce(CString, CString) -> T,
+    f: impl (FnOnce(PathBuf, PathBuf) -> io::Result<()>) + Send + 'static,
+) -> io::Result<()> {
+    let (from, to) = (from.as_ref(), to.as_ref());
+    match compio_runtime::submit(op(path_string(from)?, path_string(to)?))
+        .await
+        .0
+    {
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => asyncify_paths(from, to, f).await,
+        res => res.map(|_| ()),
+    }
+}
+
+/// Read the entire contents of a file into a bytes vector.
+///
+/// The file is opened on the blocking thread pool, and read with the positional
+/// read operation of the driver.
+///
+/// ```
+/// # compio_runtime::block_on(async {
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("hello.txt");
+/// compio_fs::write(&path, "hello").await.unwrap();
+/// assert_eq!(compio_fs::read(&path).await.unwrap(), b"hello");
+/// # })
+/// ```
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let file = OpenOptions::new().read(true).open_async(path).await?;
+    let len = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    let BufResult(res, buffer) = file.read_to_end_at(Vec::with_capacity(len), 0).await;
+    res?;
+    Ok(buffer)
+}
+
+/// Read the entire contents of a file into a string.
+///
+/// It returns an error with [`io::ErrorKind::InvalidData`] if the contents
+/// are not valid UTF-8.
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let buffer = read(path).await?;
+    String::from_utf8(buffer).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write a buffer as the entire contents of a file.
+///
+/// The file is created if it does not exist, and is truncated if it does. It is
+/// opened on the blocking thread pool. The buffer is written with the
+/// positional write operation of the driver, and is returned after the write.
+pub async fn write<T: IoBuf>(path: impl AsRef<Path>, buffer: T) -> BufResult<(), T> {
+    let options = OpenOptions::new().create(true).write(true).truncate(true);
+    let (mut file, buffer) = buf_try!(options.open_async(path).await, buffer);
+    let (_, buffer) = buf_try!(file.write_all_at(buffer, 0).await);
+    BufResult(Ok(()), buffer)
+}
+
+/// Copy the contents of one file to another, and the permission bits of the
+/// original file to the destination file. Returns the number of bytes copied.
+///
+/// See [`std::fs::copy`] for more details.
+pub async fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<u64> {
+    asyncify_paths(from, to, std::fs::copy).await
+}
+
+/// Remove a file from the filesystem.
+pub async fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        submit_path(path, |path| Unlink::new(path, false), std::fs::remove_file).await
+    }
+    #[cfg(windows)]
+    {
+        asyncify_path(path, std::fs::remove_file).await
+    }
+}
+
+/// Remove an empty directory.
+pub async fn remove_dir(path: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        submit_path(path, |path| Unlink::new(path, true), std::fs::remove_dir).await
+    }
+    #[cfg(windows)]
+    {
+        asyncify_path(path, std::fs::remove_dir).await
+    }
+}
+
+/// Remove a directory at this path, after removing all its contents. Use
+/// carefully!
+///
+/// This function does **not** follow symbolic links and it will simply remove
+/// the symbolic link itself.
+pub async fn remove_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    asyncify_path(path, std::fs::remove_dir_all).await
+}
+
+/// Create a new, empty directory at the provided path.
+pub async fn create_dir(path: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        submit_path(
+            path,
+            |path| CreateDir::new(path, 0o777),
+            std::fs::create_dir,
+        )
+        .await
+    }
+    #[cfg(windows)]
+    {
+        asyncify_path(path, std::fs::create_dir).await
+    }
+}
+
+/// Recursively create a directory and all of its parent components if they
+/// are missing.
+pub async fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    // Walk up until an existing directory, and create the missing ones down
+    // from there.
+    let mut missing = vec![];
+    for dir in path.as_ref().ancestors() {
+        if dir.as_os_str().is_empty() {
+            break;
+        }
+        match create_dir(dir).await {
+            Ok(()) => break,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => missing.push(dir),
+            Err(_) if is_dir(dir).await => break,
+            Err(e) => return Err(e),
+        }
+    }
+    for dir in missing.into_iter().rev() {
+        match create_dir(dir).await {
+            Ok(()) => {}
+            Err(_) if is_dir(dir).await => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+// The directory may be created concurrently by others.
+async fn is_dir(path: &Path) -> bool {
+    metadata(path).await.is_ok_and(|m| m.is_dir())
+}
+
+/// Rename a file or directory to a new name, replacing the original file if
+/// `to` already exists.
+pub async fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        submit_paths(from, to, Rename::new, std::fs::rename).await
+    }
+    #[cfg(windows)]
+    {
+        asyncify_paths(from, to, std::fs::rename).await
+    }
+}
+
+/// Create a new hard link on the filesystem.
+///
+/// The `link` path will be a link pointing to the `original` path.
+pub async fn hard_link(original: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        submit_paths(original, link, HardLink::new, std::fs::hard_link).await
+    }
+    #[cfg(windows)]
+    {
+        asyncify_paths(original, link, std::fs::hard_link).await
+    }
+}
+
+/// Create a new symbolic link on the filesystem.
+///
+/// The `link` path will be a symbolic link pointing to the `original` path.
+#[cfg(unix)]
+pub async fn symlink(original: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+    submit_paths(original, link, Symlink::new, std::os::unix::fs::symlink).await
+}
+
+/// Create a new symbolic link to a file on the filesystem.
+///
+/// The `link` path will be a symbolic link pointing to the `original` path.
+#[cfg(windows)]
+pub async fn symlink_file(original: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+    asyncify_paths(original, link, std::os::windows::fs::symlink_file).await
+}
+
+/// Create a new symbolic link to a directory on the filesystem.
+///
+/// The `link` path will be a symbolic link pointing to the `original` path.
+#[cfg(windows)]
+pub async fn symlink_dir(original: impl AsRef<Path>, link: impl AsRef<Path>) -> io::Result<()> {
+    asyncify_paths(original, link, std::os::windows::fs::symlink_dir).await
+}
+
+// There is `statx` in io-uring, but `Metadata` of std cannot be built from it.
+
+/// Given a path, query the file system to get information about a file,
+/// directory, etc.
+///
+/// This function will traverse symbolic links to query information about the
+/// destination file.
+pub async fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    asyncify_path(path, std::fs::metadata).await
+}
+
+/// Query the metadata about a file without following symlinks.
+pub async fn symlink_metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    asyncify_path(path, std::fs::symlink_metadata).await
+}
+
+/// Return the canonical, absolute form of a path with all intermediate
+/// components normalized and symbolic links resolved.
+pub async fn canonicalize(path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    asyncify_path(path, std::fs::canonicalize).await
+}
+
+/// Change the permissions found on a file or a directory.
+pub async fn set_permissions(path: impl AsRef<Path>, perm: Permissions) -> io::Result<()> {
+    asyncify_path(path, move |path| std::fs::set_permissions(path, perm)).await
+}
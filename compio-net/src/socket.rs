@@ -67,6 +67,22 @@ impl Socket {
         Ok(socket)
     }
 
+    #[cfg(all(
+        feature = "runtime",
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+    ))]
+    pub fn bind_reuse_port(
+        addr: &SockAddr,
+        ty: Type,
+        protocol: Option<Protocol>,
+    ) -> io::Result<Self> {
+        let socket = Self::new(addr.domain(), ty, protocol)?;
+        socket.socket.set_reuse_port(true)?;
+        socket.socket.bind(addr)?;
+        Ok(socket)
+    }
+
     pub fn listen(&self, backlog: i32) -> io::Result<()> {
         self.socket.listen(backlog)
     }
@@ -226,4 +242,8 @@ impl Attachable for Socket {
     fn is_attached(&self) -> bool {
         self.attacher.is_attached()
     }
+
+    fn detach(&mut self) -> io::Result<()> {
+        self.attacher.detach(&self.socket)
+    }
 }
@@ -67,6 +67,27 @@ impl TcpListener {
         .await
     }
 
+    /// Creates a new `TcpListener` like [`TcpListener::bind`], with
+    /// `SO_REUSEPORT` set before binding.
+    ///
+    /// Multiple listeners could be bound to the same address, and the kernel
+    /// distributes the incoming connections among them. It is useful to run
+    /// one listener per thread.
+    #[cfg(all(
+        feature = "runtime",
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+    ))]
+    pub async fn bind_reuse_port(addr: impl ToSocketAddrsAsync) -> io::Result<Self> {
+        super::each_addr(addr, |addr| async move {
+            let socket =
+                Socket::bind_reuse_port(&SockAddr::from(addr), Type::STREAM, Some(Protocol::TCP))?;
+            socket.listen(128)?;
+            Ok(Self { inner: socket })
+        })
+        .await
+    }
+
     /// Creates a new independently owned handle to the underlying socket.
     ///
     /// It does not clear the attach state.
@@ -278,3 +299,7 @@ impl_raw_fd!(TcpStream, inner);
 
 #[cfg(feature = "runtime")]
 impl_attachable!(TcpStream, inner);
+
+// Safety: the socket is thread safe apart from the attacher.
+#[cfg(feature = "runtime")]
+unsafe impl compio_runtime::Detachable for TcpStream {}
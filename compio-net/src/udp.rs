@@ -272,3 +272,7 @@ impl_raw_fd!(UdpSocket, inner);
 
 #[cfg(feature = "runtime")]
 impl_attachable!(UdpSocket, inner);
+
+// Safety: the socket is thread safe apart from the attacher.
+#[cfg(feature = "runtime")]
+unsafe impl compio_runtime::Detachable for UdpSocket {}
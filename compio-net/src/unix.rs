@@ -231,3 +231,7 @@ impl_raw_fd!(UnixStream, inner);
 
 #[cfg(feature = "runtime")]
 impl_attachable!(UnixStream, inner);
+
+// Safety: the socket is thread safe apart from the attacher.
+#[cfg(feature = "runtime")]
+unsafe impl compio_runtime::Detachable for UnixStream {}
@@ -7,7 +7,7 @@ use compio_driver::AsRawFd;
 #[cfg(not(feature = "once_cell_try"))]
 use once_cell::sync::OnceCell as OnceLock;
 
-use crate::attach;
+use crate::{attach, detach};
 
 /// Attach a handle to the driver of current thread.
 ///
@@ -43,6 +43,16 @@ impl Attacher {
         self.once.get().is_some()
     }
 
+    /// Detach the source from the driver of current thread if attached. The
+    /// source could be attached again after that.
+    pub fn detach(&mut self, source: &impl AsRawFd) -> io::Result<()> {
+        if self.is_attached() {
+            detach(source.as_raw_fd())?;
+            self.once.take();
+        }
+        Ok(())
+    }
+
     /// Try clone self with the cloned source. The attach state will be
     /// reserved.
     ///
@@ -69,8 +79,27 @@ pub trait Attachable {
 
     /// Check if [`Attachable::attach`] has been called.
     fn is_attached(&self) -> bool;
+
+    /// Detach self from the global driver. The default implementation returns
+    /// an error of [`io::ErrorKind::Unsupported`].
+    fn detach(&mut self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the resource cannot be detached",
+        ))
+    }
 }
 
+/// Marks an [`Attachable`] resource which could be moved to another thread by
+/// [`Detached`].
+///
+/// # Safety
+///
+/// The resource should be safe to send to and share with other threads when
+/// it is not attached to any driver, i.e., it is `!Send` and `!Sync` only
+/// because of its [`Attacher`].
+pub unsafe trait Detachable: Attachable {}
+
 /// A [`Send`] wrapper for attachable resource that has not been attached. The
 /// resource should be able to send to another thread before attaching.
 pub struct Unattached<T: Attachable>(T);
@@ -103,6 +132,48 @@ impl<T: Attachable> IntoInner for Unattached<T> {
 unsafe impl<T: Attachable> Send for Unattached<T> {}
 unsafe impl<T: Attachable> Sync for Unattached<T> {}
 
+/// A [`Send`] wrapper for [`Detachable`] resource that has been detached
+/// from the driver of the current thread. It is attached to the driver of the
+/// thread where it is unwrapped.
+///
+/// ## Platform specific
+/// * IOCP: a handle cannot be detached from the completion port, so
+///   [`Detached::new`] always fails for an attached resource. Only the ones
+///   never attached could be moved to another thread.
+pub struct Detached<T: Detachable>(T);
+
+impl<T: Detachable> Detached<T> {
+    /// Detach the resource from the driver of current thread. The resource is
+    /// returned with the error if it fails.
+    pub fn new(mut a: T) -> Result<Self, (io::Error, T)> {
+        match a.detach() {
+            Ok(()) => Ok(Self(a)),
+            Err(e) => Err((e, a)),
+        }
+    }
+
+    /// Attach the resource to the driver of current thread, and unwrap it.
+    pub fn attach(self) -> io::Result<T> {
+        self.0.attach()?;
+        Ok(self.0)
+    }
+}
+
+impl<T: Detachable> IntoInner for Detached<T> {
+    type Inner = T;
+
+    /// Unwrap the resource. It will be attached to the driver of current
+    /// thread lazily.
+    fn into_inner(self) -> Self::Inner {
+        self.0
+    }
+}
+
+// Safety: the resource is not attached to any driver, and it is thread safe
+// otherwise, as required by `Detachable`.
+unsafe impl<T: Detachable> Send for Detached<T> {}
+unsafe impl<T: Detachable> Sync for Detached<T> {}
+
 #[macro_export]
 #[doc(hidden)]
 macro_rules! impl_attachable {
@@ -115,6 +186,10 @@ macro_rules! impl_attachable {
             fn is_attached(&self) -> bool {
                 self.$inner.is_attached()
             }
+
+            fn detach(&mut self) -> ::std::io::Result<()> {
+                self.$inner.detach()
+            }
         }
     };
 }
@@ -0,0 +1,165 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    sync::{atomic::Ordering, Arc, Condvar, Mutex},
+    time::Duration,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::event::Event;
+
+type BoxJob = Box<dyn FnOnce() + Send>;
+
+/// The max number of threads in the blocking thread pool.
+const MAX_THREADS: usize = 512;
+
+/// The idle threads will exit after this duration.
+const KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+static POOL: Lazy<ThreadPool> = Lazy::new(|| ThreadPool::new(MAX_THREADS, KEEP_ALIVE));
+
+#[derive(Default)]
+struct PoolState {
+    queue: VecDeque<BoxJob>,
+    threads: usize,
+    // The idle threads which are not notified yet.
+    idle: usize,
+    // The notifications not consumed by the idle threads yet.
+    notified: usize,
+}
+
+/// A bounded thread pool. The threads are spawned on demand, and exit after
+/// being idle for a while.
+struct ThreadPool {
+    state: Arc<(Mutex<PoolState>, Condvar)>,
+    limit: usize,
+    keep_alive: Duration,
+}
+
+impl ThreadPool {
+    fn new(limit: usize, keep_alive: Duration) -> Self {
+        Self {
+            state: Arc::default(),
+            limit,
+            keep_alive,
+        }
+    }
+
+    fn execute(&self, job: BoxJob) {
+        let (state, condvar) = &*self.state;
+        let mut guard = state.lock().unwrap();
+        guard.queue.push_back(job);
+        // Each idle thread is notified for at most one job, otherwise a burst of
+        // jobs would run one by one on a single thread.
+        if guard.idle > 0 {
+            guard.idle -= 1;
+            guard.notified += 1;
+            condvar.notify_one();
+        } else if guard.threads < self.limit {
+            guard.threads += 1;
+            drop(guard);
+            self.spawn_worker();
+        }
+    }
+
+    fn spawn_worker(&self) {
+        let state = self.state.clone();
+        let keep_alive = self.keep_alive;
+        let res = std::thread::Builder::new()
+            .name("compio-blocking".into())
+            .spawn(move || worker(&state, keep_alive));
+        if res.is_err() {
+            // The job stays in the queue, and will be picked up by another thread.
+            self.state.0.lock().unwrap().threads -= 1;
+        }
+    }
+}
+
+fn worker((state, condvar): &(Mutex<PoolState>, Condvar), keep_alive: Duration) {
+    let mut guard = state.lock().unwrap();
+    loop {
+        if let Some(job) = guard.queue.pop_front() {
+            drop(guard);
+            job();
+            guard = state.lock().unwrap();
+            continue;
+        }
+        guard.idle += 1;
+        let (new_guard, res) = condvar.wait_timeout(guard, keep_alive).unwrap();
+        guard = new_guard;
+        // Any thread could take a notification, as they are equivalent.
+        if guard.notified > 0 {
+            guard.notified -= 1;
+        } else {
+            guard.idle -= 1;
+        }
+        if res.timed_out() && guard.queue.is_empty() {
+            guard.threads -= 1;
+            break;
+        }
+    }
+}
+
+/// Runs the provided closure on a thread where blocking is acceptable.
+///
+/// The closure is executed in a shared thread pool, with at most 512 threads.
+/// The threads are reused, and exit after being idle for 10 seconds. The
+/// runtime is woken up through an [`EventHandle`](crate::event::EventHandle)
+/// when the closure completes. If the closure panics, the panic is resumed in
+/// the awaiting task.
+///
+/// The closure starts running once this function is called, no matter the
+/// returned [`Future`] is polled or not. Dropping the [`Future`] doesn't
+/// cancel the closure.
+///
+/// # Panics
+///
+/// This function panics if the wake-up event cannot be created.
+///
+/// ```
+/// compio_runtime::block_on(async {
+///     let res = compio_runtime::spawn_blocking(|| {
+///         std::thread::sleep(std::time::Duration::from_millis(10));
+///         42
+///     })
+///     .await;
+///     assert_eq!(res, 42);
+/// })
+/// ```
+pub fn spawn_blocking<T: Send + 'static>(
+    f: impl (FnOnce() -> T) + Send + 'static,
+) -> impl Future<Output = T> {
+    let event = Event::new().expect("cannot create event for blocking task");
+    let handle = event
+        .handle()
+        .expect("cannot create event handle for blocking task");
+    let slot = Arc::new(Mutex::new(None));
+    let jobs = crate::RUNTIME.with(|runtime| runtime.blocking_jobs());
+    jobs.fetch_add(1, Ordering::AcqRel);
+    let job = {
+        let slot = slot.clone();
+        move || {
+            let res = catch_unwind(AssertUnwindSafe(f));
+            *slot.lock().unwrap() = Some(res);
+            handle.notify().ok();
+            // Decrease after the notification, so that the runtime doesn't advance the
+            // paused clock before it receives the result.
+            jobs.fetch_sub(1, Ordering::AcqRel);
+        }
+    };
+    POOL.execute(Box::new(job));
+    async move {
+        event
+            .wait()
+            .await
+            .expect("cannot wait for the event of blocking task");
+        let res = slot
+            .lock()
+            .unwrap()
+            .take()
+            .expect("the blocking task should have completed");
+        res.unwrap_or_else(|e| resume_unwind(e))
+    }
+}
@@ -0,0 +1,81 @@
+//! Channels to send values from any thread to a task on the runtime.
+//!
+//! The senders are [`Send`] and could be used in any thread, e.g., a thread
+//! pool outside the runtime. The receiver is bound to the runtime thread
+//! where the channel is created, and it is woken up through an
+//! [`Event`](crate::event::Event). The notifications are coalesced: a burst
+//! of sends before the receiver wakes up costs a single notification.
+
+pub mod mpsc;
+pub mod oneshot;
+
+use std::{
+    io,
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::event::{Event, EventHandle};
+
+/// The sending side of the coalesced notification.
+struct Notifier {
+    handle: EventHandle,
+    // Set when the event has been notified but the receiver hasn't woken up.
+    notified: AtomicBool,
+}
+
+impl Notifier {
+    fn notify(&self) {
+        if !self.notified.swap(true, Ordering::AcqRel) {
+            // The receiver fails to wake up only if the event is gone, and then
+            // nobody receives the value.
+            self.handle.notify().ok();
+        }
+    }
+
+    fn is_notified(&self) -> bool {
+        self.notified.load(Ordering::Acquire)
+    }
+}
+
+/// The receiving side of the coalesced notification. It is bound to the
+/// runtime thread.
+struct Waiter {
+    event: Event,
+    _p: PhantomData<*const ()>,
+}
+
+impl Waiter {
+    fn new() -> io::Result<(Self, Notifier)> {
+        let event = Event::new()?;
+        let notifier = Notifier {
+            handle: event.handle()?,
+            notified: AtomicBool::new(false),
+        };
+        Ok((
+            Self {
+                event,
+                _p: PhantomData,
+            },
+            notifier,
+        ))
+    }
+
+    /// Wait until `f` returns [`Some`]. `f` is checked again after the
+    /// notification is reset, so that no notification is missed.
+    async fn wait_until<R>(&self, notifier: &Notifier, mut f: impl FnMut() -> Option<R>) -> R {
+        loop {
+            if let Some(res) = f() {
+                return res;
+            }
+            notifier.notified.store(false, Ordering::Release);
+            if let Some(res) = f() {
+                return res;
+            }
+            self.event
+                .wait()
+                .await
+                .expect("cannot wait for the event of channel");
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! An unbounded multi-producer, single-consumer channel.
+//!
+//! ```
+//! use compio_runtime::channel::mpsc;
+//!
+//! compio_runtime::block_on(async {
+//!     let (tx, mut rx) = mpsc::unbounded().unwrap();
+//!     let threads = (0..4)
+//!         .map(|i| {
+//!             let tx = tx.clone();
+//!             std::thread::spawn(move || tx.send(i).unwrap())
+//!         })
+//!         .collect::<Vec<_>>();
+//!     drop(tx);
+//!     let mut sum = 0;
+//!     while let Some(i) = rx.recv().await {
+//!         sum += i;
+//!     }
+//!     assert_eq!(sum, 6);
+//!     # for t in threads { t.join().unwrap(); }
+//! })
+//! ```
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt, io,
+    sync::{Arc, Mutex},
+};
+
+use super::{Notifier, Waiter};
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    closed: bool,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    notifier: Notifier,
+}
+
+/// Create an unbounded channel. The receiver is bound to the runtime of the
+/// current thread.
+pub fn unbounded<T: Send>() -> io::Result<(Sender<T>, Receiver<T>)> {
+    let (waiter, notifier) = Waiter::new()?;
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            senders: 1,
+            closed: false,
+        }),
+        notifier,
+    });
+    Ok((
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, waiter },
+    ))
+}
+
+/// The sending half of the channel. It could be sent to and cloned in any
+/// thread.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send a value without waiting. The value is returned if the receiver is
+    /// closed.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.closed {
+                return Err(SendError(value));
+            }
+            state.queue.push_back(value);
+        }
+        self.shared.notifier.notify();
+        Ok(())
+    }
+
+    /// If the receiver is closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().unwrap().closed
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let last = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.senders -= 1;
+            state.senders == 0
+        };
+        if last {
+            self.shared.notifier.notify();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("notified", &self.shared.notifier.is_notified())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of the channel. It is bound to the runtime thread
+/// where the channel is created.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    waiter: Waiter,
+}
+
+impl<T> Receiver<T> {
+    /// Receive a value. Returns [`None`] if the channel is empty, and all
+    /// senders are dropped or the channel is closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the notification event fails.
+    pub async fn recv(&mut self) -> Option<T> {
+        self.waiter
+            .wait_until(&self.shared.notifier, || match self.try_recv() {
+                Ok(value) => Some(Some(value)),
+                Err(TryRecvError::Disconnected) => Some(None),
+                Err(TryRecvError::Empty) => None,
+            })
+            .await
+    }
+
+    /// Try to receive a value without waiting.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.queue.pop_front() {
+            Some(value) => Ok(value),
+            None if state.senders == 0 || state.closed => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Close the channel. The senders fail to send after that, but the values
+    /// sent before could still be received.
+    pub fn close(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let queue = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.closed = true;
+            std::mem::take(&mut state.queue)
+        };
+        // Drop the values in the receiver thread, outside the lock.
+        drop(queue);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("notified", &self.shared.notifier.is_notified())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Error returned by [`Sender::send`] if the receiver is closed. It contains
+/// the value.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// Error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The channel is empty.
+    Empty,
+    /// The channel is empty, and all senders are dropped or the channel is
+    /// closed.
+    Disconnected,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Disconnected => f.write_str("channel disconnected"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
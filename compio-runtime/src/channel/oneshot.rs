@@ -0,0 +1,176 @@
+//! A channel to send a single value from any thread.
+//!
+//! ```
+//! use compio_runtime::channel::oneshot;
+//!
+//! compio_runtime::block_on(async {
+//!     let (tx, rx) = oneshot::channel().unwrap();
+//!     std::thread::spawn(move || tx.send(42).unwrap());
+//!     assert_eq!(rx.recv().await, Ok(42));
+//! })
+//! ```
+
+use std::{
+    error::Error,
+    fmt, io,
+    sync::{Arc, Mutex},
+};
+
+use super::{Notifier, Waiter};
+
+struct State<T> {
+    value: Option<T>,
+    tx_dropped: bool,
+    rx_dropped: bool,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    notifier: Notifier,
+}
+
+/// Create a oneshot channel. The receiver is bound to the runtime of the
+/// current thread.
+pub fn channel<T: Send>() -> io::Result<(Sender<T>, Receiver<T>)> {
+    let (waiter, notifier) = Waiter::new()?;
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            value: None,
+            tx_dropped: false,
+            rx_dropped: false,
+        }),
+        notifier,
+    });
+    Ok((
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, waiter },
+    ))
+}
+
+/// The sending half of a oneshot channel. It could be sent to any thread.
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the value. The value is returned if the receiver is dropped.
+    pub fn send(self, value: T) -> Result<(), T> {
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.rx_dropped {
+                return Err(value);
+            }
+            state.value = Some(value);
+        }
+        // Notified when the sender is dropped.
+        Ok(())
+    }
+
+    /// If the receiver is dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().unwrap().rx_dropped
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().tx_dropped = true;
+        self.shared.notifier.notify();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+/// The receiving half of a oneshot channel. It is bound to the runtime
+/// thread where the channel is created.
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    waiter: Waiter,
+}
+
+impl<T> Receiver<T> {
+    /// Receive the value. Returns an error if the sender is dropped without
+    /// sending.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the notification event fails.
+    pub async fn recv(self) -> Result<T, RecvError> {
+        self.waiter
+            .wait_until(&self.shared.notifier, || {
+                let mut state = self.shared.state.lock().unwrap();
+                match state.value.take() {
+                    Some(value) => Some(Ok(value)),
+                    None if state.tx_dropped => Some(Err(RecvError(()))),
+                    None => None,
+                }
+            })
+            .await
+    }
+
+    /// Try to receive the value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.value.take() {
+            Some(value) => Ok(value),
+            None if state.tx_dropped => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let value = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.rx_dropped = true;
+            state.value.take()
+        };
+        drop(value);
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
+
+/// Error returned by [`Receiver::recv`] if the sender is dropped without
+/// sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl Error for RecvError {}
+
+/// Error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The value hasn't been sent yet.
+    Empty,
+    /// The sender is dropped without sending.
+    Closed,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Closed => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
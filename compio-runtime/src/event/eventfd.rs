@@ -1,56 +1,101 @@
 use std::{
+    cell::Cell,
     io,
+    mem::ManuallyDrop,
     os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
 };
 
-use compio_buf::{arrayvec::ArrayVec, BufResult};
-use compio_driver::{impl_raw_fd, op::Recv, syscall};
+use compio_buf::{arrayvec::ArrayVec, BufResult, IntoInner};
+use compio_driver::{
+    impl_raw_fd,
+    op::{BufResultExt, Recv},
+    syscall, PushEntry,
+};
+
+use crate::{attacher::Attacher, runtime::op::OpFuture, Key, RUNTIME};
 
-use crate::{attacher::Attacher, submit};
+type ReadOp = Recv<ArrayVec<u8, 8>>;
 
-/// An event that won't wake until [`EventHandle::notify`] is called
-/// successfully.
+/// The eventfd of an [`Event`](super::Event). The value accumulates the
+/// notifications, and is reset when read.
 #[derive(Debug)]
-pub struct Event {
+pub(crate) struct RawEvent {
     fd: OwnedFd,
     attacher: Attacher,
+    // The key of the read left in flight by a cancelled waiter.
+    in_flight: Cell<Option<usize>>,
 }
 
-impl Event {
-    /// Create [`Event`].
+impl RawEvent {
     pub fn new() -> io::Result<Self> {
         let fd = syscall!(libc::eventfd(0, libc::EFD_CLOEXEC))?;
         let fd = unsafe { OwnedFd::from_raw_fd(fd) };
         Ok(Self {
             fd,
             attacher: Attacher::new(),
+            in_flight: Cell::new(None),
         })
     }
 
-    /// Get a notify handle.
     pub fn handle(&self) -> io::Result<EventHandle> {
         Ok(EventHandle::new(self.fd.try_clone()?))
     }
 
-    /// Wait for [`EventHandle::notify`] called.
-    pub async fn wait(&self) -> io::Result<()> {
-        self.attacher.attach(&self.fd)?;
-        let buffer = ArrayVec::<u8, 8>::new();
-        // Trick: Recv uses readv which doesn't seek.
-        let op = Recv::new(self.as_raw_fd(), buffer);
-        let BufResult(res, _) = submit(op).await;
+    /// Wait for the notifications, and return the accumulated value.
+    ///
+    /// If the future is dropped, the read is kept in flight instead of being
+    /// cancelled, and resumed by the next call, so that the value it may have
+    /// consumed is not lost.
+    pub async fn read(&self) -> io::Result<u64> {
+        let user_data = match self.in_flight.take() {
+            // Safety: the key is submitted with `ReadOp` below.
+            Some(user_data) => unsafe { Key::<ReadOp>::new(user_data) },
+            None => {
+                self.attacher.attach(&self.fd)?;
+                let buffer = ArrayVec::<u8, 8>::new();
+                // Trick: Recv uses readv which doesn't seek.
+                let op = Recv::new(self.as_raw_fd(), buffer);
+                match RUNTIME.with(|runtime| runtime.submit_raw(op)) {
+                    PushEntry::Pending(user_data) => user_data,
+                    PushEntry::Ready(res) => return Self::parse(res),
+                }
+            }
+        };
+        self.in_flight.set(Some(*user_data));
+        // Not dropped, so that the read is not cancelled with the future.
+        let mut op = ManuallyDrop::new(OpFuture::new(user_data));
+        let res = (&mut *op).await;
+        self.in_flight.set(None);
+        Self::parse(res)
+    }
+
+    fn parse(res: BufResult<usize, ReadOp>) -> io::Result<u64> {
+        let BufResult(res, buffer) = res.into_inner().map_advanced();
         res?;
-        Ok(())
+        // The value of eventfd is always read as a whole.
+        let value = buffer
+            .into_inner()
+            .map_err(|_| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        Ok(u64::from_ne_bytes(value))
+    }
+}
+
+impl Drop for RawEvent {
+    fn drop(&mut self) {
+        if let Some(user_data) = self.in_flight.take() {
+            // Cancel the read left in flight.
+            drop(OpFuture::new(unsafe { Key::<ReadOp>::new(user_data) }));
+        }
     }
 }
 
-impl AsRawFd for Event {
+impl AsRawFd for RawEvent {
     fn as_raw_fd(&self) -> RawFd {
         self.fd.as_raw_fd()
     }
 }
 
-/// A handle to [`Event`].
+/// A handle to [`Event`](super::Event).
 pub struct EventHandle {
     fd: OwnedFd,
 }
@@ -1,44 +1,94 @@
-use std::{io, pin::Pin, ptr::null_mut, task::Poll};
+use std::{
+    io,
+    pin::Pin,
+    ptr::null_mut,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
 
 use compio_driver::{syscall, AsRawFd, OpCode, PushEntry, RawFd};
 use windows_sys::Win32::System::IO::{PostQueuedCompletionStatus, OVERLAPPED};
 
-use crate::{key::Key, runtime::op::OpFuture, RUNTIME};
+use crate::{runtime::op::OpFuture, RUNTIME};
 
-/// An event that won't wake until [`EventHandle::notify`] is called
-/// successfully.
+// The state shared by the event and its handles.
+#[derive(Debug, Default)]
+struct Shared {
+    // The key of the pending read. A notification completes it by posting the
+    // key to the driver.
+    user_data: Option<usize>,
+    // The notifications received when no read is pending.
+    pending: u64,
+}
+
+/// The completion of an [`Event`](super::Event), posted by
+/// [`EventHandle::notify`].
 #[derive(Debug)]
-pub struct Event {
-    user_data: Key<NopPending>,
+pub(crate) struct RawEvent {
+    shared: Arc<Mutex<Shared>>,
+    handle: RawFd,
 }
 
-impl Event {
-    /// Create [`Event`].
+impl RawEvent {
     pub fn new() -> io::Result<Self> {
-        let user_data = RUNTIME.with(|runtime| runtime.submit_raw(NopPending::new()));
-        let user_data = match user_data {
-            PushEntry::Pending(user_data) => user_data,
-            PushEntry::Ready(_) => unreachable!("NopPending always returns Pending"),
-        };
-        Ok(Self { user_data })
+        let handle = RUNTIME.with(|runtime| runtime.as_raw_fd());
+        Ok(Self {
+            shared: Arc::default(),
+            handle,
+        })
     }
 
-    /// Get a notify handle.
     pub fn handle(&self) -> io::Result<EventHandle> {
-        Ok(EventHandle::new(&self.user_data))
+        Ok(EventHandle {
+            shared: self.shared.clone(),
+            handle: self.handle,
+        })
     }
 
-    /// Wait for [`EventHandle::notify`] called.
-    pub async fn wait(&self) -> io::Result<()> {
-        let future = OpFuture::new(self.user_data);
-        future.await.0?;
-        Ok(())
+    /// Wait for the notifications. A fresh `NopPending` is submitted for
+    /// every read, because its key is freed after it completes.
+    pub async fn read(&self) -> io::Result<u64> {
+        // Take the pending read back if it is cancelled.
+        struct Guard<'a> {
+            event: &'a RawEvent,
+        }
+
+        impl Drop for Guard<'_> {
+            fn drop(&mut self) {
+                let mut shared = self.event.shared.lock().unwrap();
+                if let Some(user_data) = shared.user_data.take() {
+                    // Complete the cancelled operation, so that it could be freed.
+                    post_driver_nop(self.event.handle, user_data).ok();
+                } else {
+                    // The notification is consumed by the cancelled operation.
+                    shared.pending += 1;
+                }
+            }
+        }
+
+        let user_data = {
+            let mut shared = self.shared.lock().unwrap();
+            if shared.pending > 0 {
+                return Ok(std::mem::take(&mut shared.pending));
+            }
+            let user_data = match RUNTIME.with(|runtime| runtime.submit_raw(NopPending::new())) {
+                PushEntry::Pending(user_data) => user_data,
+                PushEntry::Ready(_) => unreachable!("NopPending always returns Pending"),
+            };
+            shared.user_data = Some(*user_data);
+            user_data
+        };
+        let guard = Guard { event: self };
+        let res = OpFuture::new(user_data).await.0;
+        std::mem::forget(guard);
+        res?;
+        Ok(1)
     }
 }
 
-/// A handle to [`Event`].
+/// A handle to [`Event`](super::Event).
 pub struct EventHandle {
-    user_data: usize,
+    shared: Arc<Mutex<Shared>>,
     handle: RawFd,
 }
 
@@ -47,17 +97,16 @@ unsafe impl Send for EventHandle {}
 unsafe impl Sync for EventHandle {}
 
 impl EventHandle {
-    fn new(user_data: &Key<NopPending>) -> Self {
-        let handle = RUNTIME.with(|runtime| runtime.as_raw_fd());
-        Self {
-            user_data: **user_data,
-            handle,
-        }
-    }
-
     /// Notify the event.
     pub fn notify(&self) -> io::Result<()> {
-        post_driver_nop(self.handle, self.user_data)
+        let mut shared = self.shared.lock().unwrap();
+        match shared.user_data.take() {
+            Some(user_data) => post_driver_nop(self.handle, user_data),
+            None => {
+                shared.pending += 1;
+                Ok(())
+            }
+        }
     }
 }
 
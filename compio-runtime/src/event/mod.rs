@@ -19,3 +19,234 @@ cfg_if::cfg_if! {
         pub use pipe::*;
     }
 }
+
+use std::{
+    cell::RefCell,
+    fmt,
+    future::poll_fn,
+    io,
+    task::{Poll, Waker},
+};
+
+use slab::Slab;
+
+/// The mode of an [`Event`], deciding how the notifications are consumed by
+/// the waiters.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EventMode {
+    /// The event is set by notifications, and reset when it wakes a single
+    /// waiter. The notifications before it wakes are coalesced.
+    #[default]
+    AutoReset,
+    /// The event is set by notifications, and wakes all waiters until
+    /// [`Event::reset`] is called.
+    ManualReset,
+    /// Every notification is counted. [`Event::wait`] consumes one of them,
+    /// and [`Event::wait_count`] consumes all of them.
+    Counting,
+}
+
+struct State {
+    // The notifications received and not consumed.
+    count: u64,
+    // If a waiter is reading the notifications from the driver.
+    reading: bool,
+    // Increased every time the reading completes.
+    generation: u64,
+    waiters: Slab<Option<Waker>>,
+}
+
+impl State {
+    fn wake_all(&mut self) {
+        self.generation += 1;
+        self.waiters
+            .iter_mut()
+            .filter_map(|(_, waker)| waker.take())
+            .for_each(|waker| waker.wake());
+    }
+}
+
+/// An event that won't wake until [`EventHandle::notify`] is called
+/// successfully.
+///
+/// The event could be waited by multiple tasks concurrently, and reused after
+/// it wakes. How the notifications are consumed is decided by the
+/// [`EventMode`].
+///
+/// ```
+/// use compio_runtime::event::{Event, EventMode};
+///
+/// compio_runtime::block_on(async {
+///     let event = Event::with_mode(EventMode::Counting).unwrap();
+///     let handle = event.handle().unwrap();
+///     std::thread::spawn(move || {
+///         for _ in 0..3 {
+///             handle.notify().unwrap();
+///         }
+///     })
+///     .join()
+///     .unwrap();
+///     assert_eq!(event.wait_count().await.unwrap(), 3);
+/// })
+/// ```
+pub struct Event {
+    raw: RawEvent,
+    mode: EventMode,
+    state: RefCell<State>,
+}
+
+impl Event {
+    /// Create an [`Event`] in [`EventMode::AutoReset`].
+    pub fn new() -> io::Result<Self> {
+        Self::with_mode(EventMode::AutoReset)
+    }
+
+    /// Create an [`Event`] with the mode.
+    pub fn with_mode(mode: EventMode) -> io::Result<Self> {
+        Ok(Self {
+            raw: RawEvent::new()?,
+            mode,
+            state: RefCell::new(State {
+                count: 0,
+                reading: false,
+                generation: 0,
+                waiters: Slab::new(),
+            }),
+        })
+    }
+
+    /// The mode of the event.
+    pub fn mode(&self) -> EventMode {
+        self.mode
+    }
+
+    /// Get a notify handle.
+    pub fn handle(&self) -> io::Result<EventHandle> {
+        self.raw.handle()
+    }
+
+    /// If the event is set, i.e., there are notifications not consumed.
+    pub fn is_set(&self) -> bool {
+        self.state.borrow().count > 0
+    }
+
+    /// Reset the event, and discard the notifications received. The
+    /// notifications not received from the driver yet are not discarded.
+    pub fn reset(&self) {
+        self.state.borrow_mut().count = 0;
+    }
+
+    /// Wait for [`EventHandle::notify`] called.
+    pub async fn wait(&self) -> io::Result<()> {
+        let mode = self.mode;
+        self.wait_with(|count| match mode {
+            EventMode::AutoReset => std::mem::take(count),
+            EventMode::ManualReset => *count,
+            EventMode::Counting => {
+                *count -= 1;
+                1
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Wait for [`EventHandle::notify`] called, and return the number of
+    /// notifications. The notifications are consumed unless the event is in
+    /// [`EventMode::ManualReset`].
+    pub async fn wait_count(&self) -> io::Result<u64> {
+        let mode = self.mode;
+        self.wait_with(|count| match mode {
+            EventMode::ManualReset => *count,
+            _ => std::mem::take(count),
+        })
+        .await
+    }
+
+    // Wait until there are notifications, and consume them with `f`. Only one
+    // waiter reads from the driver at a time, and the others wait for it.
+    async fn wait_with(&self, f: impl Fn(&mut u64) -> u64) -> io::Result<u64> {
+        struct Waiter<'a> {
+            state: &'a RefCell<State>,
+            key: Option<usize>,
+            reading: bool,
+        }
+
+        impl Drop for Waiter<'_> {
+            fn drop(&mut self) {
+                let mut state = self.state.borrow_mut();
+                if let Some(key) = self.key {
+                    state.waiters.remove(key);
+                }
+                if self.reading {
+                    // Let another waiter read from the driver.
+                    state.reading = false;
+                    state.wake_all();
+                }
+            }
+        }
+
+        let mut waiter = Waiter {
+            state: &self.state,
+            key: None,
+            reading: false,
+        };
+        loop {
+            let generation = {
+                let mut state = self.state.borrow_mut();
+                if state.count > 0 {
+                    return Ok(f(&mut state.count));
+                }
+                if !state.reading {
+                    state.reading = true;
+                    None
+                } else {
+                    Some(state.generation)
+                }
+            };
+            match generation {
+                None => {
+                    waiter.reading = true;
+                    let res = self.raw.read().await;
+                    waiter.reading = false;
+                    let mut state = self.state.borrow_mut();
+                    state.reading = false;
+                    state.wake_all();
+                    state.count += res?;
+                }
+                Some(generation) => {
+                    poll_fn(|cx| {
+                        let mut state = self.state.borrow_mut();
+                        if state.generation != generation {
+                            return Poll::Ready(());
+                        }
+                        let waker = Some(cx.waker().clone());
+                        match waiter.key {
+                            Some(key) => state.waiters[key] = waker,
+                            None => waiter.key = Some(state.waiters.insert(waker)),
+                        }
+                        Poll::Pending
+                    })
+                    .await
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("raw", &self.raw)
+            .field("mode", &self.mode)
+            .field("count", &self.state.borrow().count)
+            .finish()
+    }
+}
+
+#[cfg(unix)]
+impl std::os::fd::AsRawFd for Event {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.raw.as_raw_fd()
+    }
+}
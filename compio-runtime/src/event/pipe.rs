@@ -1,24 +1,29 @@
 use std::{
+    cell::Cell,
     io,
+    mem::ManuallyDrop,
     os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
 };
 
 use compio_buf::{arrayvec::ArrayVec, BufResult};
-use compio_driver::{impl_raw_fd, op::Recv, syscall};
+use compio_driver::{impl_raw_fd, op::Recv, syscall, PushEntry};
 
-use crate::{attacher::Attacher, submit};
+use crate::{attacher::Attacher, runtime::op::OpFuture, Key, RUNTIME};
 
-/// An event that won't wake until [`EventHandle::notify`] is called
-/// successfully.
+type ReadOp = Recv<ArrayVec<u8, 64>>;
+
+/// The pipe of an [`Event`](super::Event). Every notification writes a byte
+/// to the pipe.
 #[derive(Debug)]
-pub struct Event {
+pub(crate) struct RawEvent {
     sender: OwnedFd,
     receiver: OwnedFd,
     attacher: Attacher,
+    // The key of the read left in flight by a cancelled waiter.
+    in_flight: Cell<Option<usize>>,
 }
 
-impl Event {
-    /// Create [`Event`].
+impl RawEvent {
     pub fn new() -> io::Result<Self> {
         let (receiver, sender) = os_pipe::pipe()?;
         let receiver = unsafe { OwnedFd::from_raw_fd(receiver.into_raw_fd()) };
@@ -33,33 +38,77 @@ impl Event {
             sender,
             receiver,
             attacher: Attacher::new(),
+            in_flight: Cell::new(None),
         })
     }
 
-    /// Get a notify handle.
     pub fn handle(&self) -> io::Result<EventHandle> {
         Ok(EventHandle::new(self.sender.try_clone()?))
     }
 
-    /// Wait for [`EventHandle::notify`] called.
-    pub async fn wait(&self) -> io::Result<()> {
-        self.attacher.attach(&self.receiver)?;
-        let buffer = ArrayVec::<u8, 1>::new();
-        // Trick: Recv uses readv which doesn't seek.
-        let op = Recv::new(self.receiver.as_raw_fd(), buffer);
-        let BufResult(res, _) = submit(op).await;
-        res?;
-        Ok(())
+    /// Wait for the notifications, and return the number of them.
+    ///
+    /// If the future is dropped, the read is kept in flight instead of being
+    /// cancelled, and resumed by the next call, so that the bytes it may have
+    /// consumed are not lost.
+    pub async fn read(&self) -> io::Result<u64> {
+        let user_data = match self.in_flight.take() {
+            // Safety: the key is submitted with `ReadOp` below.
+            Some(user_data) => unsafe { Key::<ReadOp>::new(user_data) },
+            None => {
+                self.attacher.attach(&self.receiver)?;
+                let buffer = ArrayVec::<u8, 64>::new();
+                // Trick: Recv uses readv which doesn't seek.
+                let op = Recv::new(self.receiver.as_raw_fd(), buffer);
+                match RUNTIME.with(|runtime| runtime.submit_raw(op)) {
+                    PushEntry::Pending(user_data) => user_data,
+                    PushEntry::Ready(BufResult(res, _)) => return self.drain(res? as u64),
+                }
+            }
+        };
+        self.in_flight.set(Some(*user_data));
+        // Not dropped, so that the read is not cancelled with the future.
+        let mut op = ManuallyDrop::new(OpFuture::new(user_data));
+        let BufResult(res, _) = (&mut *op).await;
+        self.in_flight.set(None);
+        self.drain(res? as u64)
+    }
+
+    // Drain the remaining bytes, so that the count is accumulated like eventfd.
+    fn drain(&self, mut count: u64) -> io::Result<u64> {
+        let mut buffer = [0u8; 64];
+        loop {
+            match syscall!(libc::read(
+                self.receiver.as_raw_fd(),
+                buffer.as_mut_ptr() as _,
+                buffer.len()
+            )) {
+                Ok(0) => break,
+                Ok(n) => count += n as u64,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl Drop for RawEvent {
+    fn drop(&mut self) {
+        if let Some(user_data) = self.in_flight.take() {
+            // Cancel the read left in flight.
+            drop(OpFuture::new(unsafe { Key::<ReadOp>::new(user_data) }));
+        }
     }
 }
 
-impl AsRawFd for Event {
+impl AsRawFd for RawEvent {
     fn as_raw_fd(&self) -> RawFd {
         self.receiver.as_raw_fd()
     }
 }
 
-/// A handle to [`Event`].
+/// A handle to [`Event`](super::Event).
 pub struct EventHandle {
     fd: OwnedFd,
 }
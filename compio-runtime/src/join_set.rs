@@ -0,0 +1,120 @@
+use std::{
+    fmt,
+    future::Future,
+    panic,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{stream::FuturesUnordered, Stream, StreamExt};
+
+use crate::{JoinError, JoinHandle};
+
+/// A collection of tasks spawned on the runtime.
+///
+/// The tasks could be awaited in the order they complete. A panic in a task is
+/// caught and returned as a [`JoinError`]. All tasks are aborted when the set
+/// is dropped.
+///
+/// ```
+/// use compio_runtime::JoinSet;
+///
+/// compio_runtime::block_on(async {
+///     let mut set = JoinSet::new();
+///     for i in 0..10 {
+///         set.spawn(async move { i });
+///     }
+///     let mut sum = 0;
+///     while let Some(res) = set.join_next().await {
+///         sum += res.unwrap();
+///     }
+///     assert_eq!(sum, 45);
+/// })
+/// ```
+pub struct JoinSet<T> {
+    tasks: FuturesUnordered<JoinHandle<T>>,
+}
+
+impl<T: 'static> JoinSet<T> {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self {
+            tasks: FuturesUnordered::new(),
+        }
+    }
+
+    /// Spawn a task into the set. It starts running in the background
+    /// immediately.
+    pub fn spawn<F: Future<Output = T> + 'static>(&mut self, future: F) {
+        self.tasks.push(crate::spawn(future));
+    }
+
+    /// Wait for one of the tasks to complete, and return its output. Returns
+    /// [`None`] if the set is empty.
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        self.tasks.next().await
+    }
+
+    /// Wait for all tasks to complete, and collect their outputs in the order
+    /// they complete.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the panic if any of the tasks panics.
+    pub async fn join_all(mut self) -> Vec<T> {
+        let mut outputs = Vec::with_capacity(self.len());
+        while let Some(res) = self.join_next().await {
+            match res {
+                Ok(output) => outputs.push(output),
+                Err(e) => panic::resume_unwind(e.into_panic()),
+            }
+        }
+        outputs
+    }
+
+    /// Abort all tasks in the set. The aborted tasks won't be returned by
+    /// [`JoinSet::join_next`].
+    pub fn abort_all(&mut self) {
+        self.tasks.clear();
+    }
+
+    /// Detach all tasks in the set. They continue running in the background,
+    /// but their outputs are lost.
+    pub fn detach_all(&mut self) {
+        std::mem::take(&mut self.tasks)
+            .into_iter()
+            .for_each(JoinHandle::detach);
+    }
+
+    /// The number of tasks in the set.
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// If the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+
+impl<T: 'static> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static> Stream for JoinSet<T> {
+    type Item = Result<T, JoinError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.tasks.poll_next_unpin(cx)
+    }
+}
+
+impl<T> fmt::Debug for JoinSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinSet")
+            .field("len", &self.tasks.len())
+            .finish()
+    }
+}
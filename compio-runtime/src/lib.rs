@@ -15,22 +15,41 @@
 #![warn(missing_docs)]
 
 mod attacher;
+#[cfg(feature = "event")]
+mod blocking;
+mod join_set;
 mod key;
 pub(crate) mod runtime;
+mod scope;
+pub mod task;
+mod task_local;
 
+#[cfg(feature = "event")]
+pub mod channel;
 #[cfg(feature = "event")]
 pub mod event;
 #[cfg(feature = "time")]
 pub mod time;
 
-use std::{future::Future, io};
+use std::{
+    future::{poll_fn, Future},
+    io,
+    task::Poll,
+    time::Duration,
+};
 
-use async_task::Task;
 pub use attacher::*;
+#[cfg(feature = "event")]
+pub use blocking::spawn_blocking;
 use compio_buf::BufResult;
 use compio_driver::{OpCode, RawFd};
+pub use join_set::JoinSet;
 pub(crate) use key::Key;
 use runtime::Runtime;
+pub use runtime::{RuntimeBuilder, RuntimeHandle, ShutdownReport, UnhandledPanic};
+pub use scope::{scope, Scope, ScopedJoinHandle};
+pub use task::{JoinError, JoinHandle};
+pub use task_local::{AccessError, LocalKey, TaskLocalFuture};
 
 thread_local! {
     pub(crate) static RUNTIME: Runtime = Runtime::new().expect("cannot create compio runtime");
@@ -41,10 +60,12 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
     RUNTIME.with(|runtime| runtime.block_on(future))
 }
 
-/// Spawns a new asynchronous task, returning a [`Task`] for it.
+/// Spawns a new asynchronous task, returning a [`JoinHandle`] for it.
 ///
 /// Spawning a task enables the task to execute concurrently to other tasks.
 /// There is no guarantee that a spawned task will execute to completion.
+/// A panic in the task is caught and returned as a [`JoinError`], and the
+/// runtime reacts to it according to [`UnhandledPanic`].
 ///
 /// ```
 /// compio_runtime::block_on(async {
@@ -53,11 +74,91 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
 ///         42
 ///     });
 ///
-///     assert_eq!(task.await, 42);
+///     assert_eq!(task.await.unwrap(), 42);
+/// })
+/// ```
+pub fn spawn<F: Future + 'static>(future: F) -> JoinHandle<F::Output> {
+    task::Builder::new().spawn(future)
+}
+
+/// Shut down the runtime of the current thread.
+///
+/// All spawned tasks, including the detached ones, are cancelled and dropped.
+/// The tasks spawned during the shutdown, e.g., by the destructors of the
+/// cancelled ones, are cancelled as well. Then it waits at most `timeout` for
+/// the cancelled operations to complete, so that their buffers could be freed
+/// safely. The ones not completed in time are leaked.
+///
+/// The runtime stays closed after the shutdown: the tasks spawned afterwards,
+/// including the ones spawned by [`RuntimeHandle`], are cancelled when they
+/// are polled the first time, until [`reopen`] is called.
+///
+/// # Panics
+///
+/// Panics if called inside [`block_on`].
+///
+/// ```
+/// use std::time::Duration;
+///
+/// std::thread::spawn(|| {
+///     compio_runtime::block_on(async {
+///         compio_runtime::spawn(std::future::pending::<()>()).detach();
+///     });
+///     let report = compio_runtime::shutdown(Duration::from_secs(1));
+///     assert_eq!(report.cancelled_tasks, 1);
+///     assert_eq!(report.leaked_ops, 0);
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+pub fn shutdown(timeout: Duration) -> ShutdownReport {
+    RUNTIME.with(|runtime| runtime.shutdown(timeout))
+}
+
+/// Reopen the runtime of the current thread after [`shutdown`], so that it
+/// accepts new tasks again. It has no effect if the runtime is not shut down.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// std::thread::spawn(|| {
+///     compio_runtime::shutdown(Duration::from_secs(1));
+///     let task = compio_runtime::spawn(async { 42 });
+///     assert!(compio_runtime::block_on(task).unwrap_err().is_cancelled());
+///
+///     compio_runtime::reopen();
+///     let task = compio_runtime::spawn(async { 42 });
+///     assert_eq!(compio_runtime::block_on(task).unwrap(), 42);
 /// })
+/// .join()
+/// .unwrap();
 /// ```
-pub fn spawn<F: Future + 'static>(future: F) -> Task<F::Output> {
-    RUNTIME.with(|runtime| runtime.spawn(future))
+pub fn reopen() {
+    RUNTIME.with(|runtime| runtime.reopen())
+}
+
+/// Yields execution back to the runtime.
+///
+/// The current task is woken after the driver is polled, so that the IO
+/// completions and timers could make progress.
+///
+/// ```
+/// compio_runtime::block_on(async {
+///     compio_runtime::yield_now().await;
+/// })
+/// ```
+pub async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            RUNTIME.with(|runtime| runtime.defer(cx.waker()));
+            Poll::Pending
+        }
+    })
+    .await
 }
 
 /// Attach a raw file descriptor/handle/socket to the runtime.
@@ -68,6 +169,17 @@ pub fn attach(fd: RawFd) -> io::Result<()> {
     RUNTIME.with(|runtime| runtime.attach(fd))
 }
 
+/// Detach a raw file descriptor/handle/socket from the runtime, so that it
+/// could be attached to the runtime of another thread. See
+/// [`Proactor::detach`](compio_driver::Proactor::detach) for the platform
+/// specific behaviors.
+///
+/// You only need this when authoring your own high-level APIs. Use
+/// [`Detached`] for the high-level resources in this crate.
+pub fn detach(fd: RawFd) -> io::Result<()> {
+    RUNTIME.with(|runtime| runtime.detach(fd))
+}
+
 /// Submit an operation to the runtime.
 ///
 /// You only need this when authoring your own [`OpCode`].
@@ -0,0 +1,88 @@
+use crate::RuntimeHandle;
+
+/// Builder for the configuration of the runtime of the current thread.
+///
+/// There is only one runtime in each thread, so [`RuntimeBuilder::apply`]
+/// changes the configuration of the existing runtime of the current thread,
+/// instead of building a new one. The tasks already spawned are affected, too.
+///
+/// ```
+/// use compio_runtime::RuntimeBuilder;
+///
+/// let _handle = RuntimeBuilder::new().event_interval(31).budget(64).apply();
+/// compio_runtime::block_on(async {});
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuntimeBuilder {
+    pub(crate) event_interval: usize,
+    pub(crate) budget: usize,
+    pub(crate) unhandled_panic: UnhandledPanic,
+}
+
+impl RuntimeBuilder {
+    pub(crate) const DEFAULT_BUDGET: usize = 128;
+    pub(crate) const DEFAULT_EVENT_INTERVAL: usize = 61;
+
+    /// Create the builder with default config.
+    pub fn new() -> Self {
+        Self {
+            event_interval: Self::DEFAULT_EVENT_INTERVAL,
+            budget: Self::DEFAULT_BUDGET,
+            unhandled_panic: UnhandledPanic::Ignore,
+        }
+    }
+
+    /// Set the number of tasks to run before polling the driver. The driver is
+    /// polled without blocking if there are still tasks to run. Default to 61.
+    pub fn event_interval(mut self, interval: usize) -> Self {
+        self.event_interval = interval.max(1);
+        self
+    }
+
+    /// Set the number of ready operations and timers a task could consume in
+    /// one poll before it is forced to yield. Default to 128.
+    pub fn budget(mut self, budget: usize) -> Self {
+        self.budget = budget.max(1);
+        self
+    }
+
+    /// Set the behavior when a spawned task panics. Default to
+    /// [`UnhandledPanic::Ignore`].
+    pub fn unhandled_panic(mut self, behavior: UnhandledPanic) -> Self {
+        self.unhandled_panic = behavior;
+        self
+    }
+
+    /// Apply the config to the existing runtime of the current thread, and
+    /// return the handle of it.
+    pub fn apply(self) -> RuntimeHandle {
+        crate::RUNTIME.with(|runtime| {
+            runtime.configure(&self);
+            runtime.handle()
+        })
+    }
+}
+
+impl Default for RuntimeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The behavior of the runtime when a spawned task panics, and the panic is
+/// unhandled.
+///
+/// The panic is always caught and returned by the
+/// [`JoinHandle`](crate::JoinHandle) of the task. It is unhandled only if the
+/// handle has been detached or dropped, so that nobody could observe it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum UnhandledPanic {
+    /// Ignore the panic, and keep running other tasks.
+    #[default]
+    Ignore,
+    /// Abort the process.
+    Abort,
+    /// Stop running tasks, and panic in [`block_on`](crate::block_on).
+    ShutdownRuntime,
+}
@@ -0,0 +1,59 @@
+use std::{future::Future, sync::Arc};
+
+use crate::{
+    runtime::RunnableQueue,
+    task::{run, JoinHandle, TaskMeta},
+};
+
+/// A handle to the runtime of a specific thread.
+///
+/// The handle is [`Send`] and [`Sync`], and could be used to spawn tasks on
+/// the runtime thread from anywhere.
+#[derive(Clone)]
+pub struct RuntimeHandle {
+    runnables: Arc<RunnableQueue>,
+}
+
+impl RuntimeHandle {
+    pub(crate) fn new(runnables: Arc<RunnableQueue>) -> Self {
+        Self { runnables }
+    }
+
+    /// Get the handle of the runtime of the current thread.
+    pub fn current() -> Self {
+        crate::RUNTIME.with(|runtime| runtime.handle())
+    }
+
+    /// Spawns a new asynchronous task on the runtime thread, returning a
+    /// [`JoinHandle`] for it.
+    ///
+    /// The closure `f` is sent to the runtime thread and called there, so the
+    /// returned [`Future`] need not to be [`Send`]. The task will not run if
+    /// the runtime thread is not driving its runtime, e.g., not in
+    /// [`block_on`](crate::block_on).
+    ///
+    /// ```
+    /// use compio_runtime::RuntimeHandle;
+    ///
+    /// compio_runtime::block_on(async {
+    ///     let handle = RuntimeHandle::current();
+    ///     let task = std::thread::spawn(move || handle.spawn(|| async { 42 }))
+    ///         .join()
+    ///         .unwrap();
+    ///     assert_eq!(task.await.unwrap(), 42);
+    /// })
+    /// ```
+    pub fn spawn<F: Future + 'static>(
+        &self,
+        f: impl (FnOnce() -> F) + Send + 'static,
+    ) -> JoinHandle<F::Output>
+    where
+        F::Output: Send + 'static,
+    {
+        let observer = Arc::new(());
+        let future = run(async move { f().await }, None, Arc::downgrade(&observer));
+        // Safety: the future is created in the runtime thread, and it is 'static.
+        let task = unsafe { self.runnables.spawn_unchecked(future, TaskMeta::default()) };
+        JoinHandle::new(task, observer)
+    }
+}
@@ -1,75 +1,133 @@
+#[cfg(feature = "event")]
+use std::sync::atomic::AtomicUsize;
+#[cfg(all(feature = "time", feature = "event"))]
+use std::sync::atomic::Ordering;
 use std::{
-    cell::RefCell,
-    collections::VecDeque,
-    future::{ready, Future},
+    cell::{Cell, RefCell},
+    future::{poll_fn, Future},
     io,
-    task::{Context, Poll},
-    thread::ThreadId,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
 };
 
-use async_task::{Runnable, Task};
 use compio_driver::{AsRawFd, Entry, OpCode, Proactor, PushEntry, RawFd};
-use futures_util::future::Either;
+use futures_util::future::{AbortHandle, Either};
+use slab::Slab;
 use smallvec::SmallVec;
 
+mod builder;
+mod handle;
 pub(crate) mod op;
+mod queue;
+mod shutdown;
 #[cfg(feature = "time")]
 pub(crate) mod time;
 
+pub use builder::{RuntimeBuilder, UnhandledPanic};
+pub use handle::RuntimeHandle;
+use queue::RunnableQueue;
+pub use shutdown::ShutdownReport;
+
 #[cfg(feature = "time")]
-use crate::runtime::time::{TimerFuture, TimerRuntime};
+use crate::runtime::time::{TimerFuture, TimerKey, TimerRuntime};
 use crate::{
     runtime::op::{OpFuture, OpRuntime},
+    task::{run, JoinHandle, TaskGuard, TaskMeta, TaskRegistration},
     BufResult, Key,
 };
 
 pub(crate) struct Runtime {
     driver: RefCell<Proactor>,
-    thread_id: ThreadId,
-    runnables: RefCell<VecDeque<Runnable>>,
+    runnables: Arc<RunnableQueue>,
     op_runtime: RefCell<OpRuntime>,
     #[cfg(feature = "time")]
     timer_runtime: RefCell<TimerRuntime>,
+    // Wakers which should be woken after the driver is polled.
+    deferred: RefCell<Vec<Waker>>,
+    event_interval: Cell<usize>,
+    budget: Cell<usize>,
+    remaining_budget: Cell<usize>,
+    unhandled_panic: Cell<UnhandledPanic>,
+    // The description of the panicked task, if the runtime should shut down.
+    panicked: RefCell<Option<String>>,
+    // The spawned tasks which have been polled and not completed.
+    tasks: RefCell<Slab<AbortHandle>>,
+    running: Cell<bool>,
+    // If the runtime is shutting down.
+    closed: Cell<bool>,
+    // The tasks cancelled before polled, during the shutdown.
+    unpolled_cancelled: Cell<usize>,
+    // The blocking jobs spawned by this runtime and not completed.
+    #[cfg(feature = "event")]
+    blocking_jobs: Arc<AtomicUsize>,
 }
 
 impl Runtime {
     pub fn new() -> io::Result<Self> {
+        let driver = Proactor::new()?;
+        let runnables = Arc::new(RunnableQueue::new(driver.handle()?));
         Ok(Self {
-            driver: RefCell::new(Proactor::new()?),
-            thread_id: std::thread::current().id(),
-            runnables: RefCell::default(),
+            driver: RefCell::new(driver),
+            runnables,
             op_runtime: RefCell::default(),
             #[cfg(feature = "time")]
             timer_runtime: RefCell::new(TimerRuntime::new()),
+            deferred: RefCell::default(),
+            event_interval: Cell::new(RuntimeBuilder::DEFAULT_EVENT_INTERVAL),
+            budget: Cell::new(RuntimeBuilder::DEFAULT_BUDGET),
+            remaining_budget: Cell::new(RuntimeBuilder::DEFAULT_BUDGET),
+            unhandled_panic: Cell::new(UnhandledPanic::Ignore),
+            panicked: RefCell::default(),
+            tasks: RefCell::default(),
+            running: Cell::new(false),
+            closed: Cell::new(false),
+            unpolled_cancelled: Cell::new(0),
+            #[cfg(feature = "event")]
+            blocking_jobs: Arc::default(),
         })
     }
 
-    // Safety: the return runnable should be scheduled.
-    unsafe fn spawn_unchecked<F: Future>(&self, future: F) -> Task<F::Output> {
-        let schedule = move |runnable| {
-            #[cold]
-            fn panic_send_guard() -> ! {
-                panic!("Cannot wake compio waker in different threads.");
-            }
+    pub(crate) fn configure(&self, builder: &RuntimeBuilder) {
+        self.event_interval.set(builder.event_interval);
+        self.budget.set(builder.budget);
+        self.unhandled_panic.set(builder.unhandled_panic);
+    }
 
-            if self.thread_id != std::thread::current().id() {
-                panic_send_guard();
-            }
-            self.runnables.borrow_mut().push_back(runnable);
-        };
-        let (runnable, task) = async_task::spawn_unchecked(future, schedule);
-        runnable.schedule();
-        task
+    // Safety: the future should live until it completes or the task is dropped.
+    unsafe fn spawn_unchecked<F: Future>(
+        &self,
+        future: F,
+        meta: TaskMeta,
+    ) -> async_task::Task<F::Output, TaskMeta> {
+        self.runnables.spawn_unchecked(future, meta)
     }
 
     pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        struct RunningGuard<'a>(&'a Cell<bool>);
+
+        impl Drop for RunningGuard<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+
+        let _guard = RunningGuard(&self.running);
+        self.running.set(true);
         let mut result = None;
-        unsafe { self.spawn_unchecked(async { result = Some(future.await) }) }.detach();
+        unsafe { self.spawn_unchecked(async { result = Some(future.await) }, TaskMeta::default()) }
+            .detach();
         loop {
-            loop {
-                let next_task = self.runnables.borrow_mut().pop_front();
+            // Poll the driver after running `event_interval` tasks, so that the
+            // completions are not starved by the tasks waking themselves.
+            for _ in 0..self.event_interval.get() {
+                let next_task = self.runnables.pop();
                 if let Some(task) = next_task {
+                    self.remaining_budget.set(self.budget.get());
                     task.run();
+                    if let Some(task) = self.panicked.take() {
+                        panic!("{task} panicked, shutting down the runtime");
+                    }
                 } else {
                     break;
                 }
@@ -81,14 +139,103 @@ impl Runtime {
         }
     }
 
-    pub fn spawn<F: Future + 'static>(&self, future: F) -> Task<F::Output> {
-        unsafe { self.spawn_unchecked(future) }
+    pub fn spawn<F: Future + 'static>(&self, future: F, meta: TaskMeta) -> JoinHandle<F::Output> {
+        let observer = Arc::new(());
+        let future = run(future, meta.name.clone(), Arc::downgrade(&observer));
+        JoinHandle::new(unsafe { self.spawn_unchecked(future, meta) }, observer)
+    }
+
+    /// Register a task to be aborted on shutdown. Returns [`None`] if the
+    /// runtime is shutting down.
+    pub fn register_task(&self) -> Option<TaskRegistration> {
+        if self.closed.get() {
+            self.unpolled_cancelled
+                .set(self.unpolled_cancelled.get() + 1);
+            return None;
+        }
+        let (handle, registration) = AbortHandle::new_pair();
+        let key = self.tasks.borrow_mut().insert(handle);
+        Some((TaskGuard { key }, registration))
+    }
+
+    pub fn unregister_task(&self, key: usize) {
+        self.tasks.borrow_mut().try_remove(key);
+    }
+
+    pub fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        assert!(
+            !self.running.get(),
+            "cannot shut down the runtime inside `block_on`"
+        );
+        let deadline = Instant::now() + timeout;
+        self.closed.set(true);
+        self.unpolled_cancelled.set(0);
+
+        // Abort the running tasks, and run all tasks to drop them. The tasks never
+        // polled are cancelled when they are polled the first time.
+        let handles = self
+            .tasks
+            .borrow()
+            .iter()
+            .map(|(_, handle)| handle.clone())
+            .collect::<Vec<_>>();
+        for handle in &handles {
+            handle.abort();
+        }
+        while let Some(task) = self.runnables.pop() {
+            self.remaining_budget.set(self.budget.get());
+            task.run();
+        }
+        self.deferred.borrow_mut().clear();
+        let cancelled_tasks = handles.len() + self.unpolled_cancelled.get();
+
+        // Wait for the cancelled operations to complete, so that their buffers
+        // could be freed.
+        let cancelled_ops = self.op_runtime.borrow().cancelled();
+        loop {
+            let leaked_ops = self.op_runtime.borrow().cancelled();
+            let now = Instant::now();
+            if leaked_ops == 0 || now >= deadline {
+                return ShutdownReport {
+                    cancelled_tasks,
+                    cancelled_ops,
+                    leaked_ops,
+                };
+            }
+            self.poll_with(Some(deadline - now));
+        }
+    }
+
+    pub fn reopen(&self) {
+        self.closed.set(false);
+    }
+
+    pub fn on_task_panic(&self, name: Option<&str>) {
+        match self.unhandled_panic.get() {
+            UnhandledPanic::Ignore => {}
+            UnhandledPanic::Abort => std::process::abort(),
+            UnhandledPanic::ShutdownRuntime => {
+                let task = match name {
+                    Some(name) => format!("task `{name}`"),
+                    None => "a task".to_string(),
+                };
+                *self.panicked.borrow_mut() = Some(task);
+            }
+        }
+    }
+
+    pub fn handle(&self) -> RuntimeHandle {
+        RuntimeHandle::new(self.runnables.clone())
     }
 
     pub fn attach(&self, fd: RawFd) -> io::Result<()> {
         self.driver.borrow_mut().attach(fd)
     }
 
+    pub fn detach(&self, fd: RawFd) -> io::Result<()> {
+        self.driver.borrow_mut().detach(fd)
+    }
+
     pub fn submit_raw<T: OpCode + 'static>(&self, op: T) -> PushEntry<Key<T>, BufResult<usize, T>> {
         self.driver
             .borrow_mut()
@@ -99,7 +246,11 @@ impl Runtime {
     pub fn submit<T: OpCode + 'static>(&self, op: T) -> impl Future<Output = BufResult<usize, T>> {
         match self.submit_raw(op) {
             PushEntry::Pending(user_data) => Either::Left(OpFuture::new(user_data)),
-            PushEntry::Ready(res) => Either::Right(ready(res)),
+            PushEntry::Ready(res) => Either::Right(async move {
+                // The operation completes instantly, so the budget is the only chance to yield.
+                poll_fn(|cx| crate::RUNTIME.with(|runtime| runtime.poll_budget(cx))).await;
+                res
+            }),
         }
     }
 
@@ -113,13 +264,39 @@ impl Runtime {
         }
     }
 
+    #[cfg(feature = "time")]
+    pub fn timer_runtime(&self) -> std::cell::RefMut<'_, TimerRuntime> {
+        self.timer_runtime.borrow_mut()
+    }
+
+    /// Wake the waker after the driver is polled.
+    pub fn defer(&self, waker: &Waker) {
+        self.deferred.borrow_mut().push(waker.clone());
+    }
+
+    /// Consume the budget of the current task. If the budget is exhausted, the
+    /// task should yield and will be woken after the driver is polled.
+    pub fn poll_budget(&self, cx: &mut Context) -> Poll<()> {
+        let budget = self.remaining_budget.get();
+        if budget == 0 {
+            self.defer(cx.waker());
+            Poll::Pending
+        } else {
+            self.remaining_budget.set(budget - 1);
+            Poll::Ready(())
+        }
+    }
+
     pub fn cancel_op<T>(&self, user_data: Key<T>) {
-        self.driver.borrow_mut().cancel(*user_data);
-        self.op_runtime.borrow_mut().cancel(*user_data);
+        let mut driver = self.driver.borrow_mut();
+        driver.cancel(*user_data);
+        if let Some(entry) = self.op_runtime.borrow_mut().cancel(*user_data) {
+            driver.pop(&mut std::iter::once(entry)).for_each(drop);
+        }
     }
 
     #[cfg(feature = "time")]
-    pub fn cancel_timer(&self, key: usize) {
+    pub fn cancel_timer(&self, key: TimerKey) {
         self.timer_runtime.borrow_mut().cancel(key);
     }
 
@@ -130,6 +307,9 @@ impl Runtime {
     ) -> Poll<BufResult<usize, T>> {
         let mut op_runtime = self.op_runtime.borrow_mut();
         if op_runtime.has_result(*user_data) {
+            if self.poll_budget(cx).is_pending() {
+                return Poll::Pending;
+            }
             let op = op_runtime.remove(*user_data);
             let res = self
                 .driver
@@ -145,30 +325,81 @@ impl Runtime {
     }
 
     #[cfg(feature = "time")]
-    pub fn poll_timer(&self, cx: &mut Context, key: usize) -> Poll<()> {
+    pub fn poll_timer(&self, cx: &mut Context, key: TimerKey) -> Poll<()> {
         let mut timer_runtime = self.timer_runtime.borrow_mut();
         if timer_runtime.contains(key) {
             timer_runtime.update_waker(key, cx.waker().clone());
             Poll::Pending
         } else {
-            Poll::Ready(())
+            self.poll_budget(cx)
         }
     }
 
+    /// The counter of the blocking jobs which have not completed. The jobs
+    /// should decrease it after waking up the runtime.
+    #[cfg(feature = "event")]
+    pub(crate) fn blocking_jobs(&self) -> Arc<AtomicUsize> {
+        self.blocking_jobs.clone()
+    }
+
+    // The paused clock should not be advanced automatically while the blocking
+    // jobs are running, because they may complete before the timers.
+    #[cfg(all(feature = "time", feature = "event"))]
+    fn can_auto_advance(&self) -> bool {
+        self.blocking_jobs.load(Ordering::Acquire) == 0
+    }
+
+    #[cfg(all(feature = "time", not(feature = "event")))]
+    fn can_auto_advance(&self) -> bool {
+        true
+    }
+
     fn poll(&self) {
-        #[cfg(not(feature = "time"))]
-        let timeout = None;
+        // Don't block if there are tasks to run.
+        let timeout = if !self.runnables.is_empty() || !self.deferred.borrow().is_empty() {
+            Some(Duration::ZERO)
+        } else {
+            #[cfg(not(feature = "time"))]
+            let timeout = None;
+            #[cfg(feature = "time")]
+            let timeout = {
+                let timer_runtime = self.timer_runtime.borrow();
+                if timer_runtime.is_paused() && !self.can_auto_advance() {
+                    // Wait for the blocking jobs instead of spinning on the paused clock.
+                    None
+                } else {
+                    timer_runtime.min_timeout()
+                }
+            };
+            timeout
+        };
+        self.poll_with(timeout);
+
+        // Advance the paused clock if there is nothing to do.
         #[cfg(feature = "time")]
-        let timeout = self.timer_runtime.borrow().min_timeout();
+        if self.runnables.is_empty() && self.deferred.borrow().is_empty() && self.can_auto_advance()
+        {
+            let mut timer_runtime = self.timer_runtime.borrow_mut();
+            if timer_runtime.is_paused() {
+                timer_runtime.auto_advance();
+            }
+        }
+    }
 
+    fn poll_with(&self, timeout: Option<Duration>) {
         let mut entries = SmallVec::<[Entry; 1024]>::new();
         let mut driver = self.driver.borrow_mut();
         match driver.poll(timeout, &mut entries) {
             Ok(_) => {
                 for entry in entries {
-                    self.op_runtime
+                    let cancelled = self
+                        .op_runtime
                         .borrow_mut()
                         .update_result(entry.user_data(), entry);
+                    if let Some(entry) = cancelled {
+                        // Free the cancelled operation.
+                        driver.pop(&mut std::iter::once(entry)).for_each(drop);
+                    }
                 }
             }
             Err(e) => match e.kind() {
@@ -178,6 +409,9 @@ impl Runtime {
         }
         #[cfg(feature = "time")]
         self.timer_runtime.borrow_mut().wake();
+        drop(driver);
+        let deferred = std::mem::take(&mut *self.deferred.borrow_mut());
+        deferred.into_iter().for_each(Waker::wake);
     }
 }
 
@@ -186,3 +420,9 @@ impl AsRawFd for Runtime {
         self.driver.borrow().as_raw_fd()
     }
 }
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        self.runnables.close();
+    }
+}
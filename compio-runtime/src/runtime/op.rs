@@ -27,15 +27,19 @@ impl OpRuntime {
         self.ops.entry(key).or_default().waker = Some(waker);
     }
 
-    pub fn update_result(&mut self, key: usize, entry: Entry) {
+    /// Update the result of the operation. Returns the entry back if the
+    /// operation has been cancelled, and it should be popped from the driver.
+    pub fn update_result(&mut self, key: usize, entry: Entry) -> Option<Entry> {
         let op = self.ops.entry(key).or_default();
+        if op.cancelled {
+            self.ops.remove(&key);
+            return Some(entry);
+        }
         if let Some(waker) = op.waker.take() {
             waker.wake();
         }
         op.entry = Some(entry);
-        if op.cancelled {
-            self.remove(key);
-        }
+        None
     }
 
     pub fn has_result(&mut self, key: usize) -> bool {
@@ -45,8 +49,21 @@ impl OpRuntime {
             .unwrap_or_default()
     }
 
-    pub fn cancel(&mut self, key: usize) {
-        self.ops.entry(key).or_default().cancelled = true;
+    /// Cancel the operation. Returns the entry if the operation has completed,
+    /// and it should be popped from the driver.
+    pub fn cancel(&mut self, key: usize) -> Option<Entry> {
+        let op = self.ops.entry(key).or_default();
+        if op.entry.is_some() {
+            self.ops.remove(&key).and_then(|op| op.entry)
+        } else {
+            op.cancelled = true;
+            None
+        }
+    }
+
+    /// The number of cancelled operations waiting for completion.
+    pub fn cancelled(&self) -> usize {
+        self.ops.values().filter(|op| op.cancelled).count()
     }
 
     pub fn remove(&mut self, key: usize) -> RegisteredOp {
@@ -84,7 +101,10 @@ impl<T: OpCode> Future for OpFuture<T> {
 impl<T> Drop for OpFuture<T> {
     fn drop(&mut self) {
         if !self.completed {
-            crate::RUNTIME.with(|runtime| runtime.cancel_op(self.user_data))
+            // The runtime may have been destroyed with the thread.
+            crate::RUNTIME
+                .try_with(|runtime| runtime.cancel_op(self.user_data))
+                .ok();
         }
     }
 }
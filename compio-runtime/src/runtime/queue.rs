@@ -0,0 +1,123 @@
+use std::{
+    cell::UnsafeCell,
+    collections::VecDeque,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::ThreadId,
+};
+
+use async_task::{Runnable, Task};
+use compio_driver::NotifyHandle;
+
+use crate::task::TaskMeta;
+
+/// The queue of runnables of a runtime.
+///
+/// The runnables scheduled in the runtime thread are pushed into the local
+/// queue directly, while the runnables scheduled in other threads are pushed
+/// into the sync queue, and the driver is notified.
+pub(crate) struct RunnableQueue {
+    thread_id: ThreadId,
+    local_runnables: UnsafeCell<VecDeque<Runnable<TaskMeta>>>,
+    sync_runnables: Mutex<VecDeque<Runnable<TaskMeta>>>,
+    closed: AtomicBool,
+    handle: NotifyHandle,
+}
+
+// Safety: the local queue is only accessed in the runtime thread.
+unsafe impl Send for RunnableQueue {}
+unsafe impl Sync for RunnableQueue {}
+
+impl RunnableQueue {
+    pub fn new(handle: NotifyHandle) -> Self {
+        Self {
+            thread_id: std::thread::current().id(),
+            local_runnables: UnsafeCell::default(),
+            sync_runnables: Mutex::default(),
+            closed: AtomicBool::new(false),
+            handle,
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        self.thread_id == std::thread::current().id()
+    }
+
+    /// Spawn a task which is scheduled to this queue.
+    ///
+    /// # Safety
+    ///
+    /// The future should live until it completes or the task is dropped. If
+    /// the future is not [`Send`], it should be created in the runtime thread.
+    pub unsafe fn spawn_unchecked<F: Future>(
+        self: &Arc<Self>,
+        future: F,
+        meta: TaskMeta,
+    ) -> Task<F::Output, TaskMeta> {
+        let queue = self.clone();
+        let schedule = move |runnable| queue.schedule(runnable);
+        let (runnable, task) = async_task::Builder::new()
+            .metadata(meta)
+            .spawn_unchecked(|_| future, schedule);
+        runnable.schedule();
+        task
+    }
+
+    pub fn schedule(&self, runnable: Runnable<TaskMeta>) {
+        if self.closed.load(Ordering::Acquire) {
+            // The runtime has gone. The future may be not `Send`, and we cannot drop it
+            // in the current thread.
+            std::mem::forget(runnable);
+        } else if self.is_local() {
+            // Safety: only accessed in the runtime thread, and no reference is held
+            // across the calls.
+            unsafe { (*self.local_runnables.get()).push_back(runnable) }
+        } else {
+            let mut sync_runnables = self.sync_runnables.lock().unwrap();
+            // Check again under the lock, otherwise the runnable may be pushed after the
+            // queue is closed and emptied, and never be run or dropped.
+            if self.closed.load(Ordering::Acquire) {
+                drop(sync_runnables);
+                std::mem::forget(runnable);
+                return;
+            }
+            sync_runnables.push_back(runnable);
+            drop(sync_runnables);
+            self.handle.notify().ok();
+        }
+    }
+
+    /// Pop a runnable. Should only be called in the runtime thread.
+    pub fn pop(&self) -> Option<Runnable<TaskMeta>> {
+        debug_assert!(self.is_local());
+        // Safety: see `schedule`.
+        let local = unsafe { (*self.local_runnables.get()).pop_front() };
+        local.or_else(|| self.sync_runnables.lock().unwrap().pop_front())
+    }
+
+    /// Check if the queue is empty. Should only be called in the runtime
+    /// thread.
+    pub fn is_empty(&self) -> bool {
+        debug_assert!(self.is_local());
+        // Safety: see `schedule`.
+        let local_empty = unsafe { (*self.local_runnables.get()).is_empty() };
+        local_empty && self.sync_runnables.lock().unwrap().is_empty()
+    }
+
+    /// Close the queue. Should only be called in the runtime thread.
+    pub fn close(&self) {
+        debug_assert!(self.is_local());
+        let sync = {
+            // Close under the lock, so that no runnable is pushed after it is emptied.
+            let mut sync_runnables = self.sync_runnables.lock().unwrap();
+            self.closed.store(true, Ordering::Release);
+            std::mem::take(&mut *sync_runnables)
+        };
+        let local = unsafe { std::mem::take(&mut *self.local_runnables.get()) };
+        drop(local);
+        drop(sync);
+    }
+}
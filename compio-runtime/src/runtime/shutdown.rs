@@ -0,0 +1,12 @@
+/// The report of [`shutdown`](crate::shutdown).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ShutdownReport {
+    /// The number of tasks cancelled, including the ones never polled.
+    pub cancelled_tasks: usize,
+    /// The number of operations cancelled.
+    pub cancelled_ops: usize,
+    /// The number of cancelled operations not completed before the timeout.
+    /// Their buffers are leaked, because they may still be used by the kernel.
+    pub leaked_ops: usize,
+}
@@ -1,5 +1,4 @@
 use std::{
-    collections::BinaryHeap,
     future::Future,
     pin::Pin,
     task::{Context, Poll, Waker},
@@ -8,108 +7,300 @@ use std::{
 
 use slab::Slab;
 
+// The wheel ticks every millisecond. Each level has 64 slots, and a slot of
+// level `n` covers `64^n` ticks.
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+const LEVELS: usize = 6;
+// The max delay the wheel could hold. Longer timers are put into the last
+// level, and cascaded again when the slot expires.
+const MAX_DURATION: u64 = 1 << (SLOT_BITS * LEVELS as u32);
+
+/// The key of a timer. The generation makes sure that a stale key never
+/// refers to a new timer reusing the slab entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerKey {
+    index: usize,
+    generation: u64,
+}
+
 #[derive(Debug)]
 struct TimerEntry {
-    key: usize,
-    delay: Duration,
+    // The tick when the timer expires.
+    when: u64,
+    generation: u64,
+    waker: Option<Waker>,
+    // The position in the wheel, and the links of the slot list.
+    level: usize,
+    slot: usize,
+    prev: Option<usize>,
+    next: Option<usize>,
 }
 
-impl PartialEq for TimerEntry {
-    fn eq(&self, other: &Self) -> bool {
-        self.delay == other.delay
-    }
+#[derive(Debug)]
+struct Level {
+    // The bitmap of non-empty slots.
+    occupied: u64,
+    heads: [Option<usize>; SLOTS],
 }
 
-impl Eq for TimerEntry {}
+impl Level {
+    fn new() -> Self {
+        Self {
+            occupied: 0,
+            heads: [None; SLOTS],
+        }
+    }
 
-impl PartialOrd for TimerEntry {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    /// Find the next non-empty slot since `now`, and return the slot and the
+    /// tick it starts.
+    fn next_expiration(&self, level: usize, now: u64) -> Option<(usize, u64)> {
+        if self.occupied == 0 {
+            return None;
+        }
+        let slot_range = slot_range(level);
+        let level_range = slot_range * SLOTS as u64;
+        let now_slot = (now / slot_range) as u32 % SLOTS as u32;
+        let slot =
+            (self.occupied.rotate_right(now_slot).trailing_zeros() + now_slot) as usize % SLOTS;
+        let level_start = now & !(level_range - 1);
+        let mut deadline = level_start + slot as u64 * slot_range;
+        let now_slot_start = now & !(slot_range - 1);
+        // The slot is in the next round of the level. It only happens in the last
+        // level, where the timers longer than `MAX_DURATION` are put.
+        if deadline < now_slot_start || (level == LEVELS - 1 && deadline == now_slot_start) {
+            deadline += level_range;
+        }
+        Some((slot, deadline))
     }
 }
 
-impl Ord for TimerEntry {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.delay.cmp(&other.delay)
-    }
+fn slot_range(level: usize) -> u64 {
+    1 << (SLOT_BITS * level as u32)
+}
+
+fn level_for(now: u64, when: u64) -> usize {
+    let masked = (now ^ when) | (SLOTS as u64 - 1);
+    let significant = 63 - masked.leading_zeros();
+    ((significant / SLOT_BITS) as usize).min(LEVELS - 1)
 }
 
 pub struct TimerRuntime {
     time: Instant,
-    tasks: Slab<Option<Waker>>,
-    wheel: BinaryHeap<TimerEntry>,
+    // The clock is `base` plus the time since `unfrozen`. It stops if paused.
+    base: Duration,
+    unfrozen: Option<Instant>,
+    // The ticks elapsed since `time`, processed by the wheel.
+    elapsed: u64,
+    generation: u64,
+    tasks: Slab<TimerEntry>,
+    levels: [Level; LEVELS],
 }
 
 impl TimerRuntime {
     pub fn new() -> Self {
+        let time = Instant::now();
         Self {
-            time: Instant::now(),
+            time,
+            base: Duration::ZERO,
+            unfrozen: Some(time),
+            elapsed: 0,
+            generation: 0,
             tasks: Slab::default(),
-            wheel: BinaryHeap::default(),
+            levels: std::array::from_fn(|_| Level::new()),
         }
     }
 
-    pub fn contains(&self, key: usize) -> bool {
-        self.tasks.contains(key)
+    // Round up, so that the timer never fires early.
+    fn ticks_of(duration: Duration) -> u64 {
+        let ticks = duration.as_nanos().div_ceil(1_000_000);
+        ticks.try_into().unwrap_or(u64::MAX)
     }
 
-    pub fn insert(&mut self, mut delay: Duration) -> Option<usize> {
+    // The time elapsed since `time` of the clock.
+    fn clock(&self) -> Duration {
+        self.base + self.unfrozen.map(|t| t.elapsed()).unwrap_or_default()
+    }
+
+    fn now_ticks(&self) -> u64 {
+        self.clock().as_millis() as u64
+    }
+
+    /// The current time of the clock.
+    pub fn now(&self) -> Instant {
+        self.time + self.clock()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.unfrozen.is_none()
+    }
+
+    pub fn pause(&mut self) {
+        if !self.is_paused() {
+            // Align to the tick, so that the timers fire exactly when the clock is
+            // advanced by their delays.
+            self.base = Duration::from_millis(Self::ticks_of(self.clock()));
+            self.unfrozen = None;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.is_paused() {
+            self.unfrozen = Some(Instant::now());
+        }
+    }
+
+    /// Advance the paused clock, and wake the expired timers.
+    pub fn advance(&mut self, duration: Duration) {
+        assert!(self.is_paused(), "the clock is not paused");
+        self.base += duration;
+        self.wake();
+    }
+
+    /// Advance the paused clock to the next timer, and wake it.
+    pub fn auto_advance(&mut self) {
+        if let Some((_, _, deadline)) = self.next_expiration() {
+            self.base = self.base.max(Duration::from_millis(deadline));
+            self.wake();
+        }
+    }
+
+    fn get(&self, key: TimerKey) -> Option<&TimerEntry> {
+        self.tasks
+            .get(key.index)
+            .filter(|entry| entry.generation == key.generation)
+    }
+
+    pub fn contains(&self, key: TimerKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn insert(&mut self, delay: Duration) -> Option<TimerKey> {
         if delay.is_zero() {
             return None;
         }
-        let elapsed = self.time.elapsed();
-        let key = self.tasks.insert(None);
-        delay += elapsed;
-        let entry = TimerEntry { key, delay };
-        self.wheel.push(entry);
-        Some(key)
+        let when = Self::ticks_of(self.clock() + delay).max(self.elapsed);
+        self.generation += 1;
+        let generation = self.generation;
+        let index = self.tasks.insert(TimerEntry {
+            when,
+            generation,
+            waker: None,
+            level: 0,
+            slot: 0,
+            prev: None,
+            next: None,
+        });
+        self.link(index);
+        Some(TimerKey { index, generation })
+    }
+
+    pub fn update_waker(&mut self, key: TimerKey, waker: Waker) {
+        if let Some(entry) = self.tasks.get_mut(key.index) {
+            if entry.generation == key.generation {
+                entry.waker = Some(waker);
+            }
+        }
+    }
+
+    pub fn cancel(&mut self, key: TimerKey) {
+        if self.contains(key) {
+            self.unlink(key.index);
+            self.tasks.remove(key.index);
+        }
+    }
+
+    // Put the entry into the slot according to its expiration.
+    fn link(&mut self, index: usize) {
+        let entry = &self.tasks[index];
+        let when = entry.when.min(self.elapsed + MAX_DURATION - 1);
+        let level = level_for(self.elapsed, when);
+        let slot = (when >> (SLOT_BITS * level as u32)) as usize % SLOTS;
+        let head = self.levels[level].heads[slot].replace(index);
+        self.levels[level].occupied |= 1 << slot;
+        if let Some(head) = head {
+            self.tasks[head].prev = Some(index);
+        }
+        let entry = &mut self.tasks[index];
+        entry.level = level;
+        entry.slot = slot;
+        entry.prev = None;
+        entry.next = head;
     }
 
-    pub fn update_waker(&mut self, key: usize, waker: Waker) {
-        if let Some(w) = self.tasks.get_mut(key) {
-            *w = Some(waker);
+    fn unlink(&mut self, index: usize) {
+        let TimerEntry {
+            level,
+            slot,
+            prev,
+            next,
+            ..
+        } = self.tasks[index];
+        match prev {
+            Some(prev) => self.tasks[prev].next = next,
+            None => {
+                self.levels[level].heads[slot] = next;
+                if next.is_none() {
+                    self.levels[level].occupied &= !(1 << slot);
+                }
+            }
+        }
+        if let Some(next) = next {
+            self.tasks[next].prev = prev;
         }
     }
 
-    pub fn cancel(&mut self, key: usize) {
-        self.tasks.remove(key);
+    fn next_expiration(&self) -> Option<(usize, usize, u64)> {
+        self.levels.iter().enumerate().find_map(|(level, l)| {
+            l.next_expiration(level, self.elapsed)
+                .map(|(slot, deadline)| (level, slot, deadline))
+        })
     }
 
     pub fn min_timeout(&self) -> Option<Duration> {
-        let elapsed = self.time.elapsed();
-        self.wheel.peek().map(|entry| {
-            if entry.delay > elapsed {
-                entry.delay - elapsed
-            } else {
+        self.next_expiration().map(|(_, _, deadline)| {
+            if self.is_paused() {
+                // The paused clock is advanced manually, or automatically when the
+                // runtime is idle.
                 Duration::ZERO
+            } else {
+                Duration::from_millis(deadline).saturating_sub(self.clock())
             }
         })
     }
 
     pub fn wake(&mut self) {
-        let elapsed = self.time.elapsed();
-        while let Some(entry) = self.wheel.pop() {
-            if entry.delay <= elapsed {
-                if self.tasks.contains(entry.key) {
-                    if let Some(waker) = self.tasks.remove(entry.key) {
+        let now = self.now_ticks();
+        while let Some((level, slot, deadline)) = self.next_expiration() {
+            if deadline > now {
+                break;
+            }
+            self.elapsed = self.elapsed.max(deadline);
+            // Take the whole slot, and fire or cascade the entries in it.
+            let mut next = self.levels[level].heads[slot].take();
+            self.levels[level].occupied &= !(1 << slot);
+            while let Some(index) = next {
+                next = self.tasks[index].next;
+                if self.tasks[index].when <= self.elapsed {
+                    if let Some(waker) = self.tasks.remove(index).waker {
                         waker.wake();
                     }
+                } else {
+                    self.link(index);
                 }
-            } else {
-                self.wheel.push(entry);
-                break;
             }
         }
+        self.elapsed = self.elapsed.max(now);
     }
 }
 
 pub struct TimerFuture {
-    key: usize,
+    key: TimerKey,
     completed: bool,
 }
 
 impl TimerFuture {
-    pub fn new(key: usize) -> Self {
+    pub fn new(key: TimerKey) -> Self {
         Self {
             key,
             completed: false,
@@ -132,7 +323,10 @@ impl Future for TimerFuture {
 impl Drop for TimerFuture {
     fn drop(&mut self) {
         if !self.completed {
-            crate::RUNTIME.with(|runtime| runtime.cancel_timer(self.key));
+            // The runtime may have been destroyed with the thread.
+            crate::RUNTIME
+                .try_with(|runtime| runtime.cancel_timer(self.key))
+                .ok();
         }
     }
 }
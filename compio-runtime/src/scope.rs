@@ -0,0 +1,165 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use futures_util::{future::LocalBoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
+
+/// Create a scope for spawning futures that borrow non-'static data.
+///
+/// The futures spawned by [`Scope::spawn`] are owned and polled by the future
+/// returned from this function, concurrently with the future returned by `f`.
+/// It completes after all spawned futures complete, so they never outlive the
+/// borrowed data. Dropping it cancels all spawned futures.
+///
+/// ```
+/// compio_runtime::block_on(async {
+///     let mut values = vec![1, 2, 3];
+///     let sum = compio_runtime::scope(|s| {
+///         let values = &values;
+///         async move {
+///             let handle = s.spawn(async move { values.iter().sum::<i32>() });
+///             s.spawn(async move { assert_eq!(values.len(), 3) });
+///             handle.await
+///         }
+///     })
+///     .await;
+///     assert_eq!(sum, 6);
+///     values.push(4);
+/// })
+/// ```
+pub async fn scope<'env, F, Fut>(f: F) -> Fut::Output
+where
+    F: FnOnce(Scope<'env>) -> Fut,
+    Fut: Future + 'env,
+{
+    let scope = Scope {
+        pending: Rc::default(),
+    };
+    let future = f(scope.clone());
+    ScopeFuture {
+        future: Box::pin(future),
+        output: None,
+        scope,
+        running: FuturesUnordered::new(),
+    }
+    .await
+}
+
+/// A scope to spawn futures, created by [`scope`].
+pub struct Scope<'env> {
+    // Newly spawned futures, moved to the running set when the scope is polled.
+    pending: Rc<RefCell<Vec<LocalBoxFuture<'env, ()>>>>,
+}
+
+impl<'env> Scope<'env> {
+    /// Spawn a future in the scope. It runs concurrently with other futures
+    /// in the scope, even if the returned handle is not awaited.
+    pub fn spawn<F: Future + 'env>(&self, future: F) -> ScopedJoinHandle<F::Output>
+    where
+        F::Output: 'env,
+    {
+        let slot = Rc::new(RefCell::new(Slot {
+            output: None,
+            waker: None,
+        }));
+        let handle = ScopedJoinHandle { slot: slot.clone() };
+        self.pending.borrow_mut().push(
+            future
+                .map(move |output| {
+                    let mut slot = slot.borrow_mut();
+                    slot.output = Some(output);
+                    if let Some(waker) = slot.waker.take() {
+                        waker.wake();
+                    }
+                })
+                .boxed_local(),
+        );
+        handle
+    }
+}
+
+impl Clone for Scope<'_> {
+    fn clone(&self) -> Self {
+        Self {
+            pending: self.pending.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for Scope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Scope { .. }")
+    }
+}
+
+struct ScopeFuture<'env, T> {
+    future: LocalBoxFuture<'env, T>,
+    output: Option<T>,
+    scope: Scope<'env>,
+    running: FuturesUnordered<LocalBoxFuture<'env, ()>>,
+}
+
+impl<T> Unpin for ScopeFuture<'_, T> {}
+
+impl<T> Future for ScopeFuture<'_, T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        if this.output.is_none() {
+            if let Poll::Ready(output) = this.future.poll_unpin(cx) {
+                this.output = Some(output);
+            }
+        }
+        loop {
+            this.running
+                .extend(std::mem::take(&mut *this.scope.pending.borrow_mut()));
+            // Drive the spawned futures until none of them is ready.
+            while let Poll::Ready(Some(())) = this.running.poll_next_unpin(cx) {}
+            if this.scope.pending.borrow().is_empty() {
+                break;
+            }
+        }
+        if this.running.is_empty() {
+            if let Some(output) = this.output.take() {
+                return Poll::Ready(output);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+struct Slot<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// A handle to wait for the output of a future spawned by [`Scope::spawn`].
+pub struct ScopedJoinHandle<T> {
+    slot: Rc<RefCell<Slot<T>>>,
+}
+
+impl<T> Future for ScopedJoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.borrow_mut();
+        if let Some(output) = slot.output.take() {
+            Poll::Ready(output)
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> fmt::Debug for ScopedJoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ScopedJoinHandle { .. }")
+    }
+}
@@ -0,0 +1,238 @@
+//! Utilities for spawned tasks.
+
+use std::{
+    any::Any,
+    fmt,
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{Arc, Weak},
+    task::{Context, Poll},
+};
+
+use async_task::Task;
+use futures_util::{
+    future::{AbortRegistration, Abortable},
+    FutureExt,
+};
+
+/// The metadata attached to each task.
+#[derive(Debug, Default)]
+pub(crate) struct TaskMeta {
+    pub name: Option<String>,
+}
+
+/// Run the future of a spawned task. The task is registered in the runtime to
+/// be aborted on shutdown, and the panic of it is caught. The panic is reported
+/// to the runtime only if it is unhandled, i.e., the [`JoinHandle`] holding the
+/// strong reference of `observer` has been detached or dropped.
+pub(crate) async fn run<F: Future>(
+    future: F,
+    name: Option<String>,
+    observer: Weak<()>,
+) -> Result<F::Output, JoinError> {
+    let Some((_guard, registration)) = crate::RUNTIME.with(|runtime| runtime.register_task())
+    else {
+        return Err(JoinError::cancelled());
+    };
+    match Abortable::new(AssertUnwindSafe(future).catch_unwind(), registration).await {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(e)) => {
+            if observer.strong_count() == 0 {
+                crate::RUNTIME.with(|runtime| runtime.on_task_panic(name.as_deref()));
+            }
+            Err(JoinError::panic(e))
+        }
+        Err(_) => Err(JoinError::cancelled()),
+    }
+}
+
+/// Unregister the task from the runtime when dropped.
+pub(crate) struct TaskGuard {
+    pub key: usize,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        crate::RUNTIME
+            .try_with(|runtime| runtime.unregister_task(self.key))
+            .ok();
+    }
+}
+
+/// The registration of a task, see [`run`].
+pub(crate) type TaskRegistration = (TaskGuard, AbortRegistration);
+
+/// A builder to spawn tasks with options.
+///
+/// ```
+/// use compio_runtime::task::Builder;
+///
+/// compio_runtime::block_on(async {
+///     let task = Builder::new().name("answer").spawn(async { 42 });
+///     assert_eq!(task.name(), Some("answer"));
+///     assert_eq!(task.await.unwrap(), 42);
+/// })
+/// ```
+#[derive(Debug, Default)]
+pub struct Builder {
+    name: Option<String>,
+}
+
+impl Builder {
+    /// Create the builder with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name of the task.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Spawn the task with the options. See [`spawn`](crate::spawn).
+    pub fn spawn<F: Future + 'static>(self, future: F) -> JoinHandle<F::Output> {
+        crate::RUNTIME.with(|runtime| runtime.spawn(future, TaskMeta { name: self.name }))
+    }
+}
+
+/// A handle to a spawned task, returned by [`spawn`](crate::spawn).
+///
+/// Awaiting the handle gives the output of the task, or a [`JoinError`] if
+/// the task panicked or was cancelled by [`shutdown`](crate::shutdown). The
+/// task is cancelled if the handle is dropped, unless
+/// it is [detached](JoinHandle::detach).
+#[must_use = "the task is cancelled if the handle is dropped"]
+pub struct JoinHandle<T> {
+    task: Task<Result<T, JoinError>, TaskMeta>,
+    // The panic of the task is handled as long as it is alive, see [`run`].
+    _observer: Arc<()>,
+}
+
+impl<T> JoinHandle<T> {
+    pub(crate) fn new(task: Task<Result<T, JoinError>, TaskMeta>, observer: Arc<()>) -> Self {
+        Self {
+            task,
+            _observer: observer,
+        }
+    }
+
+    /// The name of the task.
+    pub fn name(&self) -> Option<&str> {
+        self.task.metadata().name.as_deref()
+    }
+
+    /// If the task has completed.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Detach the task to let it keep running in the background.
+    pub fn detach(self) {
+        self.task.detach()
+    }
+
+    /// Cancel the task and wait for it to stop. Returns the result if the task
+    /// has completed before cancelling.
+    pub async fn cancel(self) -> Option<Result<T, JoinError>> {
+        self.task.cancel().await
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.task.poll_unpin(cx)
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("name", &self.name())
+            .finish()
+    }
+}
+
+/// The error returned when a task fails to complete.
+pub struct JoinError {
+    payload: Option<Box<dyn Any + Send>>,
+}
+
+impl JoinError {
+    pub(crate) fn panic(payload: Box<dyn Any + Send>) -> Self {
+        Self {
+            payload: Some(payload),
+        }
+    }
+
+    /// Create an error of a cancelled task. It is useful for the executors
+    /// built on the runtime, which cancel the tasks by shutting down the
+    /// runtime.
+    pub fn cancelled() -> Self {
+        Self { payload: None }
+    }
+
+    /// If the task panicked.
+    pub fn is_panic(&self) -> bool {
+        self.payload.is_some()
+    }
+
+    /// If the task was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.payload.is_none()
+    }
+
+    /// Consume the error and return the panic payload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the error is not a panic.
+    pub fn into_panic(self) -> Box<dyn Any + Send> {
+        self.try_into_panic()
+            .expect("`JoinError` reason is not a panic")
+    }
+
+    /// Consume the error and return the panic payload, or the error itself if
+    /// it is not a panic.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send>, Self> {
+        match self.payload {
+            Some(payload) => Ok(payload),
+            None => Err(self),
+        }
+    }
+
+    fn message(&self) -> Option<&str> {
+        let payload = self.payload.as_ref()?;
+        payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_panic() {
+            f.debug_struct("JoinError::Panic")
+                .field("message", &self.message())
+                .finish()
+        } else {
+            f.write_str("JoinError::Cancelled")
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.is_panic(), self.message()) {
+            (true, Some(msg)) => write!(f, "task panicked with message {msg:?}"),
+            (true, None) => f.write_str("task panicked"),
+            (false, _) => f.write_str("task was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
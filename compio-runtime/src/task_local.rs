@@ -0,0 +1,198 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+    thread,
+};
+
+/// Declares a new task-local key of type [`LocalKey`].
+///
+/// The value of the key is set for a future with [`LocalKey::scope`], and it
+/// is only accessible while the future is being polled.
+///
+/// ```
+/// compio_runtime::task_local! {
+///     static REQUEST_ID: u32;
+/// }
+///
+/// compio_runtime::block_on(REQUEST_ID.scope(42, async {
+///     assert_eq!(REQUEST_ID.get(), 42);
+/// }));
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty; $($rest:tt)*) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+        $crate::task_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty) => {
+        $crate::__task_local_inner!($(#[$attr])* $vis $name, $t);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __task_local_inner {
+    ($(#[$attr:meta])* $vis:vis $name:ident, $t:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::LocalKey<$t> = {
+            ::std::thread_local! {
+                static __KEY: ::std::cell::RefCell<::std::option::Option<$t>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+
+            $crate::LocalKey { inner: __KEY }
+        };
+    };
+}
+
+/// A key for task-local data, declared by [`task_local!`].
+///
+/// The runtime is single-threaded, so the value is stored in a thread local
+/// slot, and swapped in and out every time the scoped future is polled.
+pub struct LocalKey<T: 'static> {
+    #[doc(hidden)]
+    pub inner: thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Set the value of the key for the future. The value is accessible when
+    /// the future is being polled, and is dropped with the returned future.
+    pub fn scope<F: Future>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F> {
+        TaskLocalFuture {
+            key: self,
+            slot: Some(value),
+            future: Some(future),
+        }
+    }
+
+    /// Set the value of the key while calling the closure.
+    pub fn sync_scope<F: FnOnce() -> R, R>(&'static self, value: T, f: F) -> R {
+        let mut slot = Some(value);
+        self.scope_inner(&mut slot, f)
+    }
+
+    fn scope_inner<F: FnOnce() -> R, R>(&'static self, slot: &mut Option<T>, f: F) -> R {
+        struct Guard<'a, T: 'static> {
+            key: &'static LocalKey<T>,
+            slot: &'a mut Option<T>,
+        }
+
+        impl<T: 'static> Drop for Guard<'_, T> {
+            fn drop(&mut self) {
+                // Swap the value back even if `f` panics.
+                self.key
+                    .inner
+                    .with(|inner| mem::swap(&mut *inner.borrow_mut(), self.slot));
+            }
+        }
+
+        self.inner
+            .with(|inner| mem::swap(&mut *inner.borrow_mut(), slot));
+        let _guard = Guard { key: self, slot };
+        f()
+    }
+
+    /// Access the value of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not set in the current task, or the value is
+    /// being accessed mutably in `f`.
+    pub fn with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> R {
+        self.try_with(f)
+            .expect("cannot access a task-local value without setting it")
+    }
+
+    /// Access the value of the key, or returns an error if the key is not set
+    /// in the current task.
+    pub fn try_with<F: FnOnce(&T) -> R, R>(&'static self, f: F) -> Result<R, AccessError> {
+        self.inner
+            .try_with(|inner| inner.borrow().as_ref().map(f))
+            .ok()
+            .flatten()
+            .ok_or(AccessError { _private: () })
+    }
+}
+
+impl<T: Copy + 'static> LocalKey<T> {
+    /// Get a copy of the value of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not set in the current task.
+    pub fn get(&'static self) -> T {
+        self.with(|v| *v)
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("LocalKey { .. }")
+    }
+}
+
+/// A future with a task-local value set, returned by [`LocalKey::scope`].
+pub struct TaskLocalFuture<T: 'static, F> {
+    key: &'static LocalKey<T>,
+    slot: Option<T>,
+    future: Option<F>,
+}
+
+impl<T: 'static, F: Future> Future for TaskLocalFuture<T, F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the future is never moved.
+        let this = unsafe { self.get_unchecked_mut() };
+        let key = this.key;
+        let future = &mut this.future;
+        key.scope_inner(&mut this.slot, || {
+            let inner = future
+                .as_mut()
+                .expect("`TaskLocalFuture` polled after completion");
+            let res = unsafe { Pin::new_unchecked(inner) }.poll(cx);
+            if res.is_ready() {
+                *future = None;
+            }
+            res
+        })
+    }
+}
+
+impl<T: 'static, F> Drop for TaskLocalFuture<T, F> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<F>() && self.future.is_some() {
+            // Drop the future with the value set, as it may access the value.
+            let future = &mut self.future;
+            self.key.scope_inner(&mut self.slot, || *future = None);
+        }
+    }
+}
+
+impl<T: 'static, F> fmt::Debug for TaskLocalFuture<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskLocalFuture")
+            .field("key", self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An error returned by [`LocalKey::try_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError {
+    _private: (),
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("task-local value not set")
+    }
+}
+
+impl Error for AccessError {}
@@ -2,12 +2,62 @@
 
 use std::{
     error::Error,
-    fmt::Display,
-    future::Future,
+    fmt::{self, Display},
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::{ready, Context, Poll},
     time::{Duration, Instant},
 };
 
-use futures_util::{select, FutureExt};
+use futures_util::{select, FutureExt, Stream};
+
+use crate::runtime::time::TimerFuture;
+
+/// Returns the current time of the runtime clock.
+///
+/// It is the same as [`Instant::now`] unless the clock is [paused](pause).
+pub fn now() -> Instant {
+    crate::RUNTIME.with(|runtime| runtime.timer_runtime().now())
+}
+
+/// Pauses the runtime clock of the current thread.
+///
+/// The timers only fire when the clock is advanced by [`advance`], or when the
+/// runtime has nothing to do, in which case the clock jumps to the next timer
+/// automatically. It makes the tests of timeouts run instantly and
+/// deterministically. The runtime is not considered idle while the jobs of
+/// [`spawn_blocking`](crate::spawn_blocking) are running.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use compio_runtime::time::{now, pause, sleep};
+///
+/// compio_runtime::block_on(async {
+///     pause();
+///     let start = now();
+///     sleep(Duration::from_secs(60)).await;
+///     assert_eq!(now() - start, Duration::from_secs(60));
+/// })
+/// ```
+pub fn pause() {
+    crate::RUNTIME.with(|runtime| runtime.timer_runtime().pause())
+}
+
+/// Resumes the paused runtime clock. It continues from the paused time.
+pub fn resume() {
+    crate::RUNTIME.with(|runtime| runtime.timer_runtime().resume())
+}
+
+/// Advances the paused runtime clock, and fires the expired timers.
+///
+/// # Panics
+///
+/// Panics if the clock is not paused.
+pub async fn advance(duration: Duration) {
+    crate::RUNTIME.with(|runtime| runtime.timer_runtime().advance(duration));
+    crate::yield_now().await
+}
 
 /// Waits until `duration` has elapsed.
 ///
@@ -55,7 +105,7 @@ pub async fn sleep(duration: Duration) {
 /// })
 /// ```
 pub async fn sleep_until(deadline: Instant) {
-    sleep(deadline - Instant::now()).await
+    sleep(deadline - now()).await
 }
 
 /// Error returned by [`timeout`] or [`timeout_at`].
@@ -87,7 +137,39 @@ pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Outp
 /// If the future completes before the instant is reached, then the completed
 /// value is returned. Otherwise, an error is returned.
 pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Result<F::Output, Elapsed> {
-    timeout(deadline - Instant::now(), future).await
+    timeout(deadline - now(), future).await
+}
+
+/// Defines the behavior of an [`Interval`] when it misses a tick.
+///
+/// A tick is missed if the consumer is slower than the period, e.g., the
+/// [`Interval::tick`] is called after more than one period since the last
+/// tick should have completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissedTickBehavior {
+    /// Ticks as fast as possible until caught up, and keeps the original
+    /// schedule.
+    Burst,
+    /// Ticks after a period since the missed tick completes. The schedule is
+    /// shifted.
+    Delay,
+    /// Skips the missed ticks, and ticks at the next boundary of the original
+    /// schedule.
+    #[default]
+    Skip,
+}
+
+impl MissedTickBehavior {
+    fn next_timeout(&self, timeout: Instant, now: Instant, period: Duration) -> Instant {
+        match self {
+            Self::Burst => timeout + period,
+            Self::Delay => now + period,
+            Self::Skip => {
+                now + period
+                    - Duration::from_nanos(((now - timeout).as_nanos() % period.as_nanos()) as _)
+            }
+        }
+    }
 }
 
 /// Interval returned by [`interval`] and [`interval_at`]
@@ -95,19 +177,23 @@ pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Result<F::Ou
 /// This type allows you to wait on a sequence of instants with a certain
 /// duration between each instant. Unlike calling [`sleep`] in a loop, this lets
 /// you count the time spent between the calls to [`sleep`] as well.
-#[derive(Debug)]
+///
+/// It also implements [`Stream`], yielding the instants of the ticks.
 pub struct Interval {
-    first_ticked: bool,
-    start: Instant,
+    next: Instant,
     period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
+    // The timer of the next tick, registered on the first poll.
+    delay: Option<TimerFuture>,
 }
 
 impl Interval {
     pub(crate) fn new(start: Instant, period: Duration) -> Self {
         Self {
-            first_ticked: false,
-            start,
+            next: start,
             period,
+            missed_tick_behavior: MissedTickBehavior::default(),
+            delay: None,
         }
     }
 
@@ -115,19 +201,78 @@ impl Interval {
     ///
     /// See [`interval`] and [`interval_at`].
     pub async fn tick(&mut self) -> Instant {
-        if !self.first_ticked {
-            sleep_until(self.start).await;
-            self.first_ticked = true;
-            self.start
-        } else {
-            let now = Instant::now();
-            let next = now + self.period
-                - Duration::from_nanos(
-                    ((now - self.start).as_nanos() % self.period.as_nanos()) as _,
-                );
-            sleep_until(next).await;
-            next
+        poll_fn(|cx| self.poll_tick(cx)).await
+    }
+
+    /// Polls for the next instant in the interval to be reached.
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
+        let next = self.next;
+        if self.delay.is_none() {
+            let delay = next.saturating_duration_since(now());
+            self.delay = crate::RUNTIME
+                .with(|runtime| runtime.timer_runtime().insert(delay))
+                .map(TimerFuture::new);
+        }
+        // No timer is registered if the tick is due.
+        if let Some(delay) = &mut self.delay {
+            ready!(Pin::new(delay).poll(cx));
         }
+        self.delay = None;
+
+        let now = now();
+        self.next = if now >= next + self.period {
+            self.missed_tick_behavior
+                .next_timeout(next, now, self.period)
+        } else {
+            next + self.period
+        };
+        Poll::Ready(next)
+    }
+
+    /// Resets the interval to complete one period after the current time.
+    pub fn reset(&mut self) {
+        self.reset_at(now() + self.period);
+    }
+
+    /// Resets the interval to complete at `deadline`, and ticks with the
+    /// period after it.
+    pub fn reset_at(&mut self, deadline: Instant) {
+        self.next = deadline;
+        self.delay = None;
+    }
+
+    /// Returns the [`MissedTickBehavior`] of the interval.
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.missed_tick_behavior
+    }
+
+    /// Sets the [`MissedTickBehavior`] of the interval. Default to
+    /// [`MissedTickBehavior::Skip`].
+    pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+        self.missed_tick_behavior = behavior;
+    }
+
+    /// Returns the period of the interval.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+impl Stream for Interval {
+    type Item = Instant;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_tick(cx).map(Some)
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interval")
+            .field("next", &self.next)
+            .field("period", &self.period)
+            .field("missed_tick_behavior", &self.missed_tick_behavior)
+            .finish_non_exhaustive()
     }
 }
 
@@ -195,7 +340,7 @@ impl Interval {
 /// [`sleep`]: crate::time::sleep()
 /// [`.tick().await`]: Interval::tick
 pub fn interval(period: Duration) -> Interval {
-    interval_at(Instant::now(), period)
+    interval_at(now(), period)
 }
 
 /// Creates new [`Interval`] that yields with interval of `period` with the
@@ -0,0 +1,49 @@
+//! Synchronization primitives for the tasks on the same thread.
+//!
+//! The runtime is thread-per-core, and the tasks are `!Send`, so these
+//! primitives are `!Sync`, and implemented with [`Cell`](std::cell::Cell)s
+//! and [`RefCell`](std::cell::RefCell)s instead of atomics. The waiters are
+//! woken in FIFO order.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::rc::Rc;
+//!
+//! use compio_sync::{mpsc, Mutex};
+//!
+//! compio_runtime::block_on(async {
+//!     let counter = Rc::new(Mutex::new(0));
+//!     let (tx, mut rx) = mpsc::unbounded_channel();
+//!     for _ in 0..10 {
+//!         let counter = counter.clone();
+//!         let tx = tx.clone();
+//!         compio_runtime::spawn(async move {
+//!             *counter.lock().await += 1;
+//!             tx.send(()).unwrap();
+//!         })
+//!         .detach();
+//!     }
+//!     drop(tx);
+//!     while rx.recv().await.is_some() {}
+//!     assert_eq!(*counter.lock().await, 10);
+//! })
+//! ```
+
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+#![warn(missing_docs)]
+
+mod mutex;
+mod notify;
+mod rwlock;
+mod semaphore;
+mod wait_queue;
+
+pub mod mpsc;
+pub mod oneshot;
+pub mod watch;
+
+pub use mutex::*;
+pub use notify::*;
+pub use rwlock::*;
+pub use semaphore::*;
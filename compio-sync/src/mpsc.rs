@@ -0,0 +1,428 @@
+//! Multi-producer, single-consumer channels to send values between tasks.
+//!
+//! A bounded channel created by [`channel`] waits for capacity when it is
+//! full, while an unbounded channel created by [`unbounded_channel`] never
+//! waits.
+//!
+//! ```
+//! use compio_sync::mpsc;
+//!
+//! compio_runtime::block_on(async {
+//!     let (tx, mut rx) = mpsc::channel(1);
+//!     compio_runtime::spawn(async move {
+//!         for i in 0..3 {
+//!             tx.send(i).await.unwrap();
+//!         }
+//!     })
+//!     .detach();
+//!     let mut values = vec![];
+//!     while let Some(i) = rx.recv().await {
+//!         values.push(i);
+//!     }
+//!     assert_eq!(values, [0, 1, 2]);
+//! })
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    future::poll_fn,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use futures_util::Stream;
+
+use crate::{Semaphore, TryAcquireError};
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    closed: bool,
+    rx_waker: Option<Waker>,
+}
+
+struct Chan<T> {
+    state: RefCell<State<T>>,
+    // The capacity of a bounded channel. A permit is forgotten when a value is
+    // sent, and added back when the value is received.
+    capacity: Option<Semaphore>,
+}
+
+impl<T> Chan<T> {
+    fn new(capacity: Option<Semaphore>) -> Rc<Self> {
+        Rc::new(Self {
+            state: RefCell::new(State {
+                queue: VecDeque::new(),
+                senders: 1,
+                closed: false,
+                rx_waker: None,
+            }),
+            capacity,
+        })
+    }
+
+    fn push(&self, value: T) -> Result<(), T> {
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            if state.closed {
+                return Err(value);
+            }
+            state.queue.push_back(value);
+            state.rx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.state.borrow().closed
+    }
+
+    fn add_sender(&self) {
+        self.state.borrow_mut().senders += 1;
+    }
+
+    fn drop_sender(&self) {
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            state.senders -= 1;
+            if state.senders == 0 {
+                state.rx_waker.take()
+            } else {
+                None
+            }
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.state.borrow_mut();
+        match state.queue.pop_front() {
+            Some(value) => {
+                drop(state);
+                if let Some(capacity) = &self.capacity {
+                    capacity.add_permits(1);
+                }
+                Ok(value)
+            }
+            None if state.senders == 0 || state.closed => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => {
+                self.state.borrow_mut().rx_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    fn close(&self) {
+        self.state.borrow_mut().closed = true;
+        if let Some(capacity) = &self.capacity {
+            capacity.close();
+        }
+    }
+}
+
+/// Create a bounded channel with the capacity of `buffer` values.
+///
+/// # Panics
+///
+/// Panics if `buffer` is zero or exceeds [`Semaphore::MAX_PERMITS`].
+pub fn channel<T>(buffer: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(buffer > 0, "mpsc bounded channel requires buffer > 0");
+    let chan = Chan::new(Some(Semaphore::new(buffer)));
+    (Sender { chan: chan.clone() }, Receiver { chan })
+}
+
+/// Create an unbounded channel.
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, UnboundedReceiver<T>) {
+    let chan = Chan::new(None);
+    (
+        UnboundedSender { chan: chan.clone() },
+        UnboundedReceiver { chan },
+    )
+}
+
+/// The sending half of a bounded channel.
+pub struct Sender<T> {
+    chan: Rc<Chan<T>>,
+}
+
+impl<T> Sender<T> {
+    fn semaphore(&self) -> &Semaphore {
+        self.chan.capacity.as_ref().unwrap()
+    }
+
+    /// Send a value, and wait for capacity if the channel is full. The value
+    /// is returned if the receiver is closed.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.semaphore().acquire().await {
+            Ok(permit) => {
+                permit.forget();
+                self.chan.push(value).map_err(SendError)
+            }
+            Err(_) => Err(SendError(value)),
+        }
+    }
+
+    /// Try to send a value without waiting.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        match self.semaphore().try_acquire() {
+            Ok(permit) => {
+                permit.forget();
+                self.chan.push(value).map_err(TrySendError::Closed)
+            }
+            Err(TryAcquireError::NoPermits) => Err(TrySendError::Full(value)),
+            Err(TryAcquireError::Closed) => Err(TrySendError::Closed(value)),
+        }
+    }
+
+    /// The number of values that could be sent without waiting.
+    pub fn capacity(&self) -> usize {
+        self.semaphore().available_permits()
+    }
+
+    /// If the receiver is closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.chan.is_closed()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.add_sender();
+        Self {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.chan.drop_sender();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+/// The receiving half of a bounded channel.
+pub struct Receiver<T> {
+    chan: Rc<Chan<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receive a value. Returns [`None`] if the channel is empty, and all
+    /// senders are dropped or the channel is closed.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.chan.poll_recv(cx)).await
+    }
+
+    /// Try to receive a value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.chan.try_recv()
+    }
+
+    /// Close the channel. The senders fail to send after that, but the values
+    /// sent before could still be received.
+    pub fn close(&mut self) {
+        self.chan.close();
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.chan.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.chan.close();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
+
+/// The sending half of an unbounded channel.
+pub struct UnboundedSender<T> {
+    chan: Rc<Chan<T>>,
+}
+
+impl<T> UnboundedSender<T> {
+    /// Send a value without waiting. The value is returned if the receiver is
+    /// closed.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.chan.push(value).map_err(SendError)
+    }
+
+    /// If the receiver is closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.chan.is_closed()
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        self.chan.add_sender();
+        Self {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        self.chan.drop_sender();
+    }
+}
+
+impl<T> fmt::Debug for UnboundedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("UnboundedSender { .. }")
+    }
+}
+
+/// The receiving half of an unbounded channel.
+pub struct UnboundedReceiver<T> {
+    chan: Rc<Chan<T>>,
+}
+
+impl<T> UnboundedReceiver<T> {
+    /// Receive a value. Returns [`None`] if the channel is empty, and all
+    /// senders are dropped or the channel is closed.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.chan.poll_recv(cx)).await
+    }
+
+    /// Try to receive a value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.chan.try_recv()
+    }
+
+    /// Close the channel. The senders fail to send after that, but the values
+    /// sent before could still be received.
+    pub fn close(&mut self) {
+        self.chan.close();
+    }
+}
+
+impl<T> Stream for UnboundedReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.chan.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for UnboundedReceiver<T> {
+    fn drop(&mut self) {
+        self.chan.close();
+    }
+}
+
+impl<T> fmt::Debug for UnboundedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("UnboundedReceiver { .. }")
+    }
+}
+
+/// Error returned when sending to a closed channel. It contains the value.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// Error returned by [`Sender::try_send`]. It contains the value.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is full.
+    Full(T),
+    /// The receiver is closed.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Get the value back.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(value) | Self::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.pad("Full(..)"),
+            Self::Closed(_) => f.pad("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("channel full"),
+            Self::Closed(_) => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl<T> Error for TrySendError<T> {}
+
+/// Error returned by `try_recv` of the receivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The channel is empty.
+    Empty,
+    /// The channel is empty, and all senders are dropped or the channel is
+    /// closed.
+    Disconnected,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Disconnected => f.write_str("channel disconnected"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
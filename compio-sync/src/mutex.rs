@@ -0,0 +1,128 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::Semaphore;
+
+/// An asynchronous mutual exclusion lock, which could be held across `.await`
+/// points.
+///
+/// The lock is granted in FIFO order. It is `!Sync`, so it only synchronizes
+/// the tasks on the same thread, without any atomic operations.
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use compio_sync::Mutex;
+///
+/// compio_runtime::block_on(async {
+///     let mutex = Rc::new(Mutex::new(0));
+///     let tasks = (0..10)
+///         .map(|_| {
+///             let mutex = mutex.clone();
+///             compio_runtime::spawn(async move {
+///                 *mutex.lock().await += 1;
+///             })
+///         })
+///         .collect::<Vec<_>>();
+///     for task in tasks {
+///         task.await.unwrap();
+///     }
+///     assert_eq!(*mutex.lock().await, 10);
+/// })
+/// ```
+pub struct Mutex<T: ?Sized> {
+    semaphore: Semaphore,
+    value: UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+    /// Create a new unlocked mutex.
+    pub fn new(value: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(1),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the mutex, and return the inner value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Lock the mutex, and wait if it is locked by others.
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        // The semaphore is never closed.
+        self.semaphore.acquire().await.unwrap().forget();
+        MutexGuard { mutex: self }
+    }
+
+    /// Try to lock the mutex without waiting.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.semaphore.try_acquire().ok().map(|permit| {
+            permit.forget();
+            MutexGuard { mutex: self }
+        })
+    }
+
+    /// Get a mutable reference to the inner value. No locking is needed
+    /// because it is borrowed mutably.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Some(guard) => d.field("value", &&*guard),
+            None => d.field("value", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// A guard of a locked [`Mutex`]. The lock is released when it is dropped.
+#[must_use = "if unused the mutex will immediately unlock"]
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the guard holds the only permit.
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the guard holds the only permit.
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.semaphore.add_permits(1);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
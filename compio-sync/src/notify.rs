@@ -0,0 +1,181 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::wait_queue::WaitQueue;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Notification {
+    None,
+    One,
+    All,
+}
+
+struct State {
+    // A permit stored by `notify_one` when there is no waiter.
+    permit: bool,
+    // Increased by every `notify_waiters`.
+    generation: u64,
+    waiters: WaitQueue<Notification>,
+}
+
+/// Notify a single task or all waiting tasks to wake up.
+///
+/// [`Notify::notify_one`] stores a permit if no task is waiting, and the next
+/// call to [`Notify::notified`] completes immediately. At most one permit is
+/// stored.
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use compio_sync::Notify;
+///
+/// compio_runtime::block_on(async {
+///     let notify = Rc::new(Notify::new());
+///     let task = compio_runtime::spawn({
+///         let notify = notify.clone();
+///         async move { notify.notified().await }
+///     });
+///     notify.notify_one();
+///     task.await.unwrap();
+/// })
+/// ```
+pub struct Notify {
+    state: RefCell<State>,
+}
+
+impl Notify {
+    /// Create a notify without a permit.
+    pub fn new() -> Self {
+        Self {
+            state: RefCell::new(State {
+                permit: false,
+                generation: 0,
+                waiters: WaitQueue::new(),
+            }),
+        }
+    }
+
+    /// Wait for a notification.
+    ///
+    /// The returned future is notified by [`Notify::notify_waiters`] since it
+    /// is created, even if it hasn't been polled. It is queued for
+    /// [`Notify::notify_one`] when it is polled the first time.
+    pub fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            generation: self.state.borrow().generation,
+            key: None,
+        }
+    }
+
+    /// Notify the first waiting task, or store a permit for the next one.
+    pub fn notify_one(&self) {
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            match state.waiters.pop_front() {
+                Some(key) => {
+                    *state.waiters.get_mut(key) = Notification::One;
+                    state.waiters.take_waker(key)
+                }
+                None => {
+                    state.permit = true;
+                    None
+                }
+            }
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Notify all waiting tasks. No permit is stored.
+    pub fn notify_waiters(&self) {
+        let wakers = {
+            let mut state = self.state.borrow_mut();
+            state.generation += 1;
+            state.waiters.drain(|n| *n = Notification::All)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notify")
+            .field("permit", &self.state.borrow().permit)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`Notify::notified`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Notified<'a> {
+    notify: &'a Notify,
+    generation: u64,
+    key: Option<usize>,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let notify = self.notify;
+        let mut state = notify.state.borrow_mut();
+        match self.key {
+            None => {
+                if state.generation != self.generation {
+                    Poll::Ready(())
+                } else if state.permit {
+                    state.permit = false;
+                    Poll::Ready(())
+                } else {
+                    self.key = Some(
+                        state
+                            .waiters
+                            .push_back(Notification::None, cx.waker().clone()),
+                    );
+                    Poll::Pending
+                }
+            }
+            Some(key) => {
+                if *state.waiters.get_mut(key) != Notification::None {
+                    state.waiters.remove(key);
+                    self.key = None;
+                    Poll::Ready(())
+                } else {
+                    state.waiters.set_waker(key, cx.waker());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let notification = self.notify.state.borrow_mut().waiters.remove(key);
+            // Pass the unconsumed notification to the next waiter.
+            if notification == Notification::One {
+                self.notify.notify_one();
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Notified<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Notified { .. }")
+    }
+}
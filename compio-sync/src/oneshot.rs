@@ -0,0 +1,201 @@
+//! A channel to send a single value between tasks.
+//!
+//! ```
+//! use compio_sync::oneshot;
+//!
+//! compio_runtime::block_on(async {
+//!     let (tx, rx) = oneshot::channel();
+//!     compio_runtime::spawn(async move {
+//!         tx.send(42).unwrap();
+//!     })
+//!     .detach();
+//!     assert_eq!(rx.await, Ok(42));
+//! })
+//! ```
+
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::{poll_fn, Future},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+struct Inner<T> {
+    value: Option<T>,
+    tx_dropped: bool,
+    rx_closed: bool,
+    rx_waker: Option<Waker>,
+    tx_waker: Option<Waker>,
+}
+
+/// Create a oneshot channel.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Rc::new(RefCell::new(Inner {
+        value: None,
+        tx_dropped: false,
+        rx_closed: false,
+        rx_waker: None,
+        tx_waker: None,
+    }));
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver { inner },
+    )
+}
+
+/// The sending half of a oneshot channel.
+pub struct Sender<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Send the value. The value is returned if the receiver is closed.
+    pub fn send(self, value: T) -> Result<(), T> {
+        let waker = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.rx_closed {
+                return Err(value);
+            }
+            inner.value = Some(value);
+            inner.rx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// If the receiver is closed or dropped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.borrow().rx_closed
+    }
+
+    /// Wait until the receiver is closed or dropped.
+    pub async fn closed(&mut self) {
+        poll_fn(|cx| {
+            let mut inner = self.inner.borrow_mut();
+            if inner.rx_closed {
+                Poll::Ready(())
+            } else {
+                inner.tx_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut inner = self.inner.borrow_mut();
+            inner.tx_dropped = true;
+            inner.rx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Sender { .. }")
+    }
+}
+
+/// The receiving half of a oneshot channel. Await it to receive the value.
+pub struct Receiver<T> {
+    inner: Rc<RefCell<Inner<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Try to receive the value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut inner = self.inner.borrow_mut();
+        match inner.value.take() {
+            Some(value) => Ok(value),
+            None if inner.tx_dropped => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Close the channel. The sender fails to send after that, but the value
+    /// sent before could still be received.
+    pub fn close(&mut self) {
+        let waker = {
+            let mut inner = self.inner.borrow_mut();
+            inner.rx_closed = true;
+            inner.tx_waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut inner = self.inner.borrow_mut();
+        match inner.value.take() {
+            Some(value) => Poll::Ready(Ok(value)),
+            None if inner.tx_dropped => Poll::Ready(Err(RecvError(()))),
+            None => {
+                inner.rx_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Receiver { .. }")
+    }
+}
+
+/// Error returned by awaiting a [`Receiver`], if the sender is dropped
+/// without sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl Error for RecvError {}
+
+/// Error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The value hasn't been sent yet.
+    Empty,
+    /// The sender is dropped without sending.
+    Closed,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("channel empty"),
+            Self::Closed => f.write_str("channel closed"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
@@ -0,0 +1,174 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::Semaphore;
+
+// A reader holds one permit, and a writer holds all of them.
+const MAX_READS: usize = 1 << 20;
+
+/// An asynchronous reader-writer lock.
+///
+/// The lock is granted in FIFO order, so a waiting writer blocks the readers
+/// coming after it, and is never starved. It is `!Sync`, so it only
+/// synchronizes the tasks on the same thread.
+///
+/// ```
+/// use compio_sync::RwLock;
+///
+/// compio_runtime::block_on(async {
+///     let lock = RwLock::new(1);
+///     {
+///         let a = lock.read().await;
+///         let b = lock.read().await;
+///         assert_eq!(*a + *b, 2);
+///         assert!(lock.try_write().is_none());
+///     }
+///     *lock.write().await += 1;
+///     assert_eq!(*lock.read().await, 2);
+/// })
+/// ```
+pub struct RwLock<T: ?Sized> {
+    semaphore: Semaphore,
+    value: UnsafeCell<T>,
+}
+
+impl<T> RwLock<T> {
+    /// Create a new unlocked lock.
+    pub fn new(value: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(MAX_READS),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consume the lock, and return the inner value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Lock with shared read access, and wait if there is a writer holding or
+    /// waiting for the lock.
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        // The semaphore is never closed.
+        self.semaphore.acquire().await.unwrap().forget();
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Try to lock with shared read access without waiting.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        self.semaphore.try_acquire().ok().map(|permit| {
+            permit.forget();
+            RwLockReadGuard { lock: self }
+        })
+    }
+
+    /// Lock with exclusive write access, and wait if there are other holders.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.semaphore
+            .acquire_many(MAX_READS)
+            .await
+            .unwrap()
+            .forget();
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Try to lock with exclusive write access without waiting.
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        self.semaphore
+            .try_acquire_many(MAX_READS)
+            .ok()
+            .map(|permit| {
+                permit.forget();
+                RwLockWriteGuard { lock: self }
+            })
+    }
+
+    /// Get a mutable reference to the inner value. No locking is needed
+    /// because it is borrowed mutably.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Some(guard) => d.field("value", &&*guard),
+            None => d.field("value", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// A guard of shared read access to a [`RwLock`].
+#[must_use = "if unused the lock will immediately unlock"]
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: there is no writer when a reader holds the lock.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.add_permits(1);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// A guard of exclusive write access to a [`RwLock`].
+#[must_use = "if unused the lock will immediately unlock"]
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the writer holds all permits.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the writer holds all permits.
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.add_permits(MAX_READS);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::wait_queue::WaitQueue;
+
+struct Waiter {
+    needed: usize,
+    // The permits have been assigned, and the waiter is unlinked.
+    assigned: bool,
+}
+
+struct State {
+    permits: usize,
+    closed: bool,
+    waiters: WaitQueue<Waiter>,
+}
+
+impl State {
+    // Assign the permits to the waiters in FIFO order, and return the wakers to
+    // wake after the borrow is released.
+    fn dispatch(&mut self) -> Vec<Waker> {
+        let mut wakers = vec![];
+        while let Some(waiter) = self.waiters.front_mut() {
+            if waiter.needed > self.permits {
+                break;
+            }
+            self.permits -= waiter.needed;
+            waiter.assigned = true;
+            let key = self.waiters.pop_front().unwrap();
+            wakers.extend(self.waiters.take_waker(key));
+        }
+        wakers
+    }
+}
+
+/// A counting semaphore, with fair FIFO order of acquiring.
+///
+/// A large acquire blocks the later ones, even if there are enough permits for
+/// them.
+///
+/// ```
+/// use compio_sync::Semaphore;
+///
+/// compio_runtime::block_on(async {
+///     let semaphore = Semaphore::new(2);
+///     let a = semaphore.acquire().await.unwrap();
+///     let b = semaphore.acquire().await.unwrap();
+///     assert!(semaphore.try_acquire().is_err());
+///     drop(a);
+///     assert_eq!(semaphore.available_permits(), 1);
+///     # drop(b);
+/// })
+/// ```
+pub struct Semaphore {
+    state: RefCell<State>,
+}
+
+impl Semaphore {
+    /// The max number of permits of a semaphore.
+    pub const MAX_PERMITS: usize = usize::MAX >> 3;
+
+    /// Create a semaphore with the initial number of permits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permits` exceeds [`Semaphore::MAX_PERMITS`].
+    pub fn new(permits: usize) -> Self {
+        assert!(
+            permits <= Self::MAX_PERMITS,
+            "a semaphore may not have more than {} permits",
+            Self::MAX_PERMITS
+        );
+        Self {
+            state: RefCell::new(State {
+                permits,
+                closed: false,
+                waiters: WaitQueue::new(),
+            }),
+        }
+    }
+
+    /// The number of permits available now.
+    pub fn available_permits(&self) -> usize {
+        self.state.borrow().permits
+    }
+
+    /// Add permits to the semaphore, and wake the waiters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the permits exceed [`Semaphore::MAX_PERMITS`].
+    pub fn add_permits(&self, n: usize) {
+        let wakers = {
+            let mut state = self.state.borrow_mut();
+            state.permits = state
+                .permits
+                .checked_add(n)
+                .filter(|permits| *permits <= Self::MAX_PERMITS)
+                .expect("permits overflow");
+            state.dispatch()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Close the semaphore. All pending and future acquires fail, but the
+    /// acquired permits are still valid.
+    pub fn close(&self) {
+        let wakers = {
+            let mut state = self.state.borrow_mut();
+            state.closed = true;
+            state.waiters.drain(|_| {})
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// If the semaphore is closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.borrow().closed
+    }
+
+    /// Acquire a permit.
+    pub fn acquire(&self) -> Acquire<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Acquire `n` permits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds [`Semaphore::MAX_PERMITS`].
+    pub fn acquire_many(&self, n: usize) -> Acquire<'_> {
+        assert!(n <= Self::MAX_PERMITS, "acquiring too many permits");
+        Acquire {
+            semaphore: self,
+            needed: n,
+            key: None,
+        }
+    }
+
+    /// Try to acquire a permit without waiting.
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_acquire_many(1)
+    }
+
+    /// Try to acquire `n` permits without waiting. It fails if any waiter is
+    /// queued, to keep the order fair.
+    pub fn try_acquire_many(&self, n: usize) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        let mut state = self.state.borrow_mut();
+        if state.closed {
+            Err(TryAcquireError::Closed)
+        } else if state.waiters.is_empty() && state.permits >= n {
+            state.permits -= n;
+            Ok(SemaphorePermit::new(self, n))
+        } else {
+            Err(TryAcquireError::NoPermits)
+        }
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("Semaphore")
+            .field("permits", &state.permits)
+            .field("closed", &state.closed)
+            .finish()
+    }
+}
+
+/// Future returned by [`Semaphore::acquire`] and [`Semaphore::acquire_many`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    needed: usize,
+    key: Option<usize>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = Result<SemaphorePermit<'a>, AcquireError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore;
+        let mut state = semaphore.state.borrow_mut();
+        match self.key {
+            None => {
+                if state.closed {
+                    return Poll::Ready(Err(AcquireError(())));
+                }
+                if state.waiters.is_empty() && state.permits >= self.needed {
+                    state.permits -= self.needed;
+                    return Poll::Ready(Ok(SemaphorePermit::new(semaphore, self.needed)));
+                }
+                let waiter = Waiter {
+                    needed: self.needed,
+                    assigned: false,
+                };
+                self.key = Some(state.waiters.push_back(waiter, cx.waker().clone()));
+                Poll::Pending
+            }
+            Some(key) => {
+                if state.waiters.get_mut(key).assigned {
+                    state.waiters.remove(key);
+                    self.key = None;
+                    Poll::Ready(Ok(SemaphorePermit::new(semaphore, self.needed)))
+                } else if state.closed {
+                    state.waiters.remove(key);
+                    self.key = None;
+                    Poll::Ready(Err(AcquireError(())))
+                } else {
+                    state.waiters.set_waker(key, cx.waker());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let wakers = {
+                let mut state = self.semaphore.state.borrow_mut();
+                let waiter = state.waiters.remove(key);
+                if waiter.assigned {
+                    state.permits += waiter.needed;
+                }
+                // The waiters behind may be satisfied now.
+                state.dispatch()
+            };
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+}
+
+impl fmt::Debug for Acquire<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Acquire")
+            .field("needed", &self.needed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Permits acquired from a [`Semaphore`]. They are released when dropped.
+#[must_use = "the permits are released immediately if unused"]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl<'a> SemaphorePermit<'a> {
+    fn new(semaphore: &'a Semaphore, permits: usize) -> Self {
+        Self { semaphore, permits }
+    }
+
+    /// The number of permits held.
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Forget the permits without releasing them to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
+
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
+
+/// Error returned by [`Semaphore::acquire`] if the semaphore is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcquireError(());
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("semaphore closed")
+    }
+}
+
+impl Error for AcquireError {}
+
+/// Error returned by [`Semaphore::try_acquire`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryAcquireError {
+    /// The semaphore is closed.
+    Closed,
+    /// There are not enough permits available.
+    NoPermits,
+}
+
+impl fmt::Display for TryAcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Closed => f.write_str("semaphore closed"),
+            Self::NoPermits => f.write_str("no permits available"),
+        }
+    }
+}
+
+impl Error for TryAcquireError {}
@@ -0,0 +1,113 @@
+use std::task::Waker;
+
+use slab::Slab;
+
+struct Node<T> {
+    data: T,
+    waker: Option<Waker>,
+    // The links of the queue. A node is unlinked when it is popped, but it
+    // stays in the slab until the owner removes it.
+    linked: bool,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+/// A FIFO queue of waiters. Each waiter is owned by a future, which keeps
+/// the key and removes the node when it completes or is dropped.
+pub(crate) struct WaitQueue<T> {
+    nodes: Slab<Node<T>>,
+    head: Option<usize>,
+    tail: Option<usize>,
+}
+
+impl<T> WaitQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Slab::new(),
+            head: None,
+            tail: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn push_back(&mut self, data: T, waker: Waker) -> usize {
+        let key = self.nodes.insert(Node {
+            data,
+            waker: Some(waker),
+            linked: true,
+            prev: self.tail,
+            next: None,
+        });
+        match self.tail {
+            Some(tail) => self.nodes[tail].next = Some(key),
+            None => self.head = Some(key),
+        }
+        self.tail = Some(key);
+        key
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|head| &mut self.nodes[head].data)
+    }
+
+    /// Unlink the first waiter, and return its key. The node is kept.
+    pub fn pop_front(&mut self) -> Option<usize> {
+        let head = self.head?;
+        self.unlink(head);
+        Some(head)
+    }
+
+    pub fn get_mut(&mut self, key: usize) -> &mut T {
+        &mut self.nodes[key].data
+    }
+
+    pub fn is_linked(&self, key: usize) -> bool {
+        self.nodes[key].linked
+    }
+
+    pub fn set_waker(&mut self, key: usize, waker: &Waker) {
+        let slot = &mut self.nodes[key].waker;
+        if !slot.as_ref().is_some_and(|w| w.will_wake(waker)) {
+            *slot = Some(waker.clone());
+        }
+    }
+
+    pub fn take_waker(&mut self, key: usize) -> Option<Waker> {
+        self.nodes[key].waker.take()
+    }
+
+    /// Remove the waiter, and return its data.
+    pub fn remove(&mut self, key: usize) -> T {
+        if self.nodes[key].linked {
+            self.unlink(key);
+        }
+        self.nodes.remove(key).data
+    }
+
+    /// Unlink all waiters, and return their wakers.
+    pub fn drain(&mut self, mut f: impl FnMut(&mut T)) -> Vec<Waker> {
+        let mut wakers = vec![];
+        while let Some(key) = self.pop_front() {
+            f(self.get_mut(key));
+            wakers.extend(self.take_waker(key));
+        }
+        wakers
+    }
+
+    fn unlink(&mut self, key: usize) {
+        let node = &mut self.nodes[key];
+        let (prev, next) = (node.prev.take(), node.next.take());
+        node.linked = false;
+        match prev {
+            Some(prev) => self.nodes[prev].next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.nodes[next].prev = prev,
+            None => self.tail = prev,
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! A single-producer, multi-consumer channel that only keeps the latest
+//! value.
+//!
+//! The receivers are notified when a new value is sent, and they could
+//! borrow the value at any time.
+//!
+//! ```
+//! use compio_sync::watch;
+//!
+//! compio_runtime::block_on(async {
+//!     let (tx, mut rx) = watch::channel("hello");
+//!     compio_runtime::spawn(async move {
+//!         tx.send("world").unwrap();
+//!     })
+//!     .detach();
+//!     rx.changed().await.unwrap();
+//!     assert_eq!(*rx.borrow_and_update(), "world");
+//! })
+//! ```
+
+use std::{
+    cell::{Ref, RefCell},
+    error::Error,
+    fmt,
+    future::poll_fn,
+    mem,
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+use crate::wait_queue::WaitQueue;
+
+struct State {
+    // Increased by every sent value.
+    version: u64,
+    receivers: usize,
+    tx_dropped: bool,
+    // The waiters of `Receiver::changed` and `Sender::closed`.
+    waiters: WaitQueue<()>,
+}
+
+struct Shared<T> {
+    value: RefCell<T>,
+    state: RefCell<State>,
+}
+
+impl<T> Shared<T> {
+    fn wake_all(&self) {
+        let wakers = self.state.borrow_mut().waiters.drain(|_| {});
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    // Wait until the condition is met. The waiter is removed if the future is
+    // dropped.
+    async fn wait_until<R>(&self, mut f: impl FnMut(&State) -> Option<R>) -> R {
+        struct Guard<'a> {
+            state: &'a RefCell<State>,
+            key: Option<usize>,
+        }
+
+        impl Drop for Guard<'_> {
+            fn drop(&mut self) {
+                if let Some(key) = self.key {
+                    self.state.borrow_mut().waiters.remove(key);
+                }
+            }
+        }
+
+        let mut guard = Guard {
+            state: &self.state,
+            key: None,
+        };
+        poll_fn(|cx| {
+            let mut state = self.state.borrow_mut();
+            if let Some(res) = f(&state) {
+                return Poll::Ready(res);
+            }
+            match guard.key {
+                Some(key) if state.waiters.is_linked(key) => {
+                    state.waiters.set_waker(key, cx.waker());
+                }
+                key => {
+                    if let Some(key) = key {
+                        state.waiters.remove(key);
+                    }
+                    guard.key = Some(state.waiters.push_back((), cx.waker().clone()));
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+/// Create a watch channel with the initial value.
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Rc::new(Shared {
+        value: RefCell::new(init),
+        state: RefCell::new(State {
+            version: 0,
+            receivers: 1,
+            tx_dropped: false,
+            waiters: WaitQueue::new(),
+        }),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, version: 0 },
+    )
+}
+
+/// The sending half of a watch channel.
+pub struct Sender<T> {
+    shared: Rc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Send a new value, and notify the receivers. The value is returned if
+    /// there is no receiver.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.receiver_count() == 0 {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Send a new value even if there is no receiver, and return the old one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is borrowed.
+    pub fn send_replace(&self, value: T) -> T {
+        let old = mem::replace(&mut *self.shared.value.borrow_mut(), value);
+        self.shared.state.borrow_mut().version += 1;
+        self.shared.wake_all();
+        old
+    }
+
+    /// Modify the value in place, and notify the receivers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is borrowed.
+    pub fn send_modify(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.shared.value.borrow_mut());
+        self.shared.state.borrow_mut().version += 1;
+        self.shared.wake_all();
+    }
+
+    /// Borrow the current value.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.shared.value.borrow()
+    }
+
+    /// Create a new receiver, which has seen the current value.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.shared.state.borrow_mut();
+        state.receivers += 1;
+        Receiver {
+            shared: self.shared.clone(),
+            version: state.version,
+        }
+    }
+
+    /// The number of receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.shared.state.borrow().receivers
+    }
+
+    /// If all receivers are dropped.
+    pub fn is_closed(&self) -> bool {
+        self.receiver_count() == 0
+    }
+
+    /// Wait until all receivers are dropped.
+    pub async fn closed(&self) {
+        self.shared
+            .wait_until(|state| (state.receivers == 0).then_some(()))
+            .await
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.state.borrow_mut().tx_dropped = true;
+        self.shared.wake_all();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+/// The receiving half of a watch channel.
+pub struct Receiver<T> {
+    shared: Rc<Shared<T>>,
+    // The version seen by the receiver.
+    version: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Borrow the current value without marking it as seen.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.shared.value.borrow()
+    }
+
+    /// Borrow the current value, and mark it as seen.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.version = self.shared.state.borrow().version;
+        self.shared.value.borrow()
+    }
+
+    /// If there is a new value not seen yet. Returns an error if the sender
+    /// is dropped.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let state = self.shared.state.borrow();
+        if state.tx_dropped {
+            Err(RecvError(()))
+        } else {
+            Ok(state.version != self.version)
+        }
+    }
+
+    /// Wait for a new value not seen yet, and mark it as seen. Returns an
+    /// error if the sender is dropped.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        let seen = self.version;
+        let res = self
+            .shared
+            .wait_until(|state| {
+                if state.version != seen {
+                    Some(Ok(state.version))
+                } else if state.tx_dropped {
+                    Some(Err(RecvError(())))
+                } else {
+                    None
+                }
+            })
+            .await;
+        self.version = res?;
+        Ok(())
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.borrow_mut().receivers += 1;
+        Self {
+            shared: self.shared.clone(),
+            version: self.version,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let closed = {
+            let mut state = self.shared.state.borrow_mut();
+            state.receivers -= 1;
+            state.receivers == 0
+        };
+        if closed {
+            self.shared.wake_all();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+/// Error returned by [`Sender::send`] if there is no receiver. It contains
+/// the value.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// Error returned by [`Receiver::changed`] if the sender is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl Error for RecvError {}
@@ -0,0 +1,62 @@
+use std::{
+    future::{poll_fn, Future},
+    pin::pin,
+    task::Poll,
+    time::Duration,
+};
+
+use compio::runtime::time::sleep;
+use criterion::{async_executor::AsyncExecutor, criterion_group, criterion_main, Criterion};
+
+criterion_group! {
+    name = time;
+    config = Criterion::default().sample_size(10);
+    targets = set_cancel
+}
+criterion_main!(time);
+
+struct CompioRuntime;
+
+impl AsyncExecutor for CompioRuntime {
+    fn block_on<T>(&self, future: impl std::future::Future<Output = T>) -> T {
+        compio::runtime::block_on(future)
+    }
+}
+
+const TIMERS: u64 = 1_000_000;
+
+fn set_cancel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("set_cancel");
+
+    group.bench_function("tokio", |b| {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        b.to_async(&runtime).iter(|| async {
+            for i in 0..TIMERS {
+                let mut timer = pin!(tokio::time::sleep(Duration::from_millis(i)));
+                poll_fn(|cx| {
+                    let _ = timer.as_mut().poll(cx);
+                    Poll::Ready(())
+                })
+                .await;
+            }
+        })
+    });
+
+    group.bench_function("compio", |b| {
+        b.to_async(CompioRuntime).iter(|| async {
+            for i in 0..TIMERS {
+                let mut timer = pin!(sleep(Duration::from_millis(i)));
+                poll_fn(|cx| {
+                    let _ = timer.as_mut().poll(cx);
+                    Poll::Ready(())
+                })
+                .await;
+            }
+        })
+    });
+
+    group.finish();
+}
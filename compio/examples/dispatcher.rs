@@ -31,24 +31,28 @@ async fn main() {
         }));
         while let Some(()) = futures.next().await {}
     });
+    let mut handles = FuturesUnordered::new();
     for _i in 0..CLIENT_NUM {
         let (srv, _) = listener.accept().await.unwrap();
         let srv = Unattached::new(srv).unwrap();
-        dispatcher
+        let handle = dispatcher
             .dispatch(move || {
                 let mut srv = srv.into_inner();
                 async move {
                     let BufResult(res, buf) = srv.read(Vec::with_capacity(20)).await;
-                    res?;
+                    res.unwrap();
                     println!("{}", std::str::from_utf8(&buf).unwrap());
-                    Ok(())
                 }
             })
             .unwrap();
+        handles.push(handle);
+    }
+    while let Some(res) = handles.next().await {
+        res.unwrap();
     }
     // Dispatcher::join is a blocking call, which may block the main thread. We need
     // to wait for the client first.
-    task.await;
+    task.await.unwrap();
     for res in dispatcher.join() {
         res.unwrap();
     }
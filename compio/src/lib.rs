@@ -37,6 +37,12 @@ pub use compio_runtime as runtime;
 #[cfg(feature = "signal")]
 #[doc(inline)]
 pub use compio_signal as signal;
+#[cfg(feature = "sync")]
+#[doc(inline)]
+pub use compio_sync as sync;
+#[cfg(feature = "event")]
+#[doc(no_inline)]
+pub use runtime::channel;
 #[cfg(feature = "event")]
 #[doc(no_inline)]
 pub use runtime::event;
@@ -0,0 +1,50 @@
+use compio::buf::{AlignedBuf, IoBuf, IoBufMut, SetBufInit};
+
+#[test]
+fn aligned_buf_grow() {
+    let mut buf = AlignedBuf::new(4096);
+    assert_eq!(buf.capacity(), 0);
+    assert!(buf.is_empty());
+
+    for i in 0..10000u32 {
+        buf.extend_from_slice(&i.to_le_bytes());
+        assert_eq!(buf.as_buf_ptr() as usize % 4096, 0);
+        assert_eq!(buf.capacity() % 4096, 0);
+    }
+    assert_eq!(buf.len(), 40000);
+    assert!(buf
+        .chunks(4)
+        .enumerate()
+        .all(|(i, b)| b == (i as u32).to_le_bytes()));
+
+    let cloned = buf.clone();
+    assert_eq!(cloned.as_slice(), buf.as_slice());
+    assert_eq!(cloned.as_buf_ptr() as usize % 4096, 0);
+
+    buf.truncate(4);
+    assert_eq!(buf.as_slice(), 0u32.to_le_bytes());
+    buf.clear();
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn aligned_buf_set_init() {
+    let mut buf = AlignedBuf::with_capacity(1, 512);
+    assert_eq!(buf.buf_capacity(), 512);
+    assert_eq!(buf.as_mut_slice().len(), 512);
+
+    unsafe {
+        buf.as_buf_mut_ptr().write_bytes(7, 512);
+        buf.set_buf_init(512);
+        // A shorter length is ignored.
+        buf.set_buf_init(10);
+    }
+    assert_eq!(buf.len(), 512);
+    assert!(buf.iter().all(|b| *b == 7));
+}
+
+#[test]
+#[should_panic]
+fn aligned_buf_bad_align() {
+    AlignedBuf::new(3);
+}
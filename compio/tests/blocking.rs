@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use compio::runtime::spawn_blocking;
+use futures_util::{stream::FuturesUnordered, StreamExt};
+
+#[compio_macros::test]
+async fn blocking() {
+    let thread_id = std::thread::current().id();
+    let res = spawn_blocking(move || {
+        assert_ne!(std::thread::current().id(), thread_id);
+        std::thread::sleep(Duration::from_millis(100));
+        42
+    })
+    .await;
+    assert_eq!(res, 42);
+}
+
+#[compio_macros::test]
+async fn blocking_many() {
+    let mut tasks = (0..64)
+        .map(|i| {
+            spawn_blocking(move || {
+                std::thread::sleep(Duration::from_millis(10));
+                i
+            })
+        })
+        .collect::<FuturesUnordered<_>>();
+    let mut sum = 0;
+    while let Some(i) = tasks.next().await {
+        sum += i;
+    }
+    assert_eq!(sum, (0..64).sum::<i32>());
+}
+
+#[compio_macros::test]
+async fn blocking_wait_each_other() {
+    // Leave an idle thread in the pool.
+    spawn_blocking(|| {}).await;
+
+    let (tx1, rx1) = std::sync::mpsc::channel();
+    let (tx2, rx2) = std::sync::mpsc::channel();
+    let task1 = spawn_blocking(move || {
+        tx1.send(()).unwrap();
+        rx2.recv_timeout(Duration::from_secs(5)).is_ok()
+    });
+    let task2 = spawn_blocking(move || {
+        tx2.send(()).unwrap();
+        rx1.recv_timeout(Duration::from_secs(5)).is_ok()
+    });
+    assert!(task1.await);
+    assert!(task2.await);
+}
+
+#[compio_macros::test]
+#[should_panic(expected = "blocking panic")]
+async fn blocking_panic() {
+    spawn_blocking(|| panic!("blocking panic")).await
+}
@@ -0,0 +1,166 @@
+use std::{
+    future::{poll_fn, Future},
+    pin::pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+use compio::channel::{mpsc, oneshot};
+use futures_util::task::{waker, ArcWake};
+
+struct CountingWaker {
+    inner: Waker,
+    wakes: Arc<AtomicUsize>,
+}
+
+impl ArcWake for CountingWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.wakes.fetch_add(1, Ordering::Relaxed);
+        arc_self.inner.wake_by_ref();
+    }
+}
+
+// Count the wake-ups of the future.
+async fn counted<F: Future>(future: F, wakes: Arc<AtomicUsize>) -> F::Output {
+    let mut future = pin!(future);
+    poll_fn(|cx| {
+        let waker = waker(Arc::new(CountingWaker {
+            inner: cx.waker().clone(),
+            wakes: wakes.clone(),
+        }));
+        future
+            .as_mut()
+            .poll(&mut std::task::Context::from_waker(&waker))
+    })
+    .await
+}
+
+#[compio_macros::test]
+async fn mpsc_burst() {
+    let (tx, mut rx) = mpsc::unbounded().unwrap();
+    let threads = (0..4)
+        .map(|t| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..10000 {
+                    tx.send((t, i)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    // The values from the same thread are received in order.
+    let mut next = [0; 4];
+    while let Some((t, i)) = rx.recv().await {
+        assert_eq!(next[t], i);
+        next[t] += 1;
+    }
+    assert_eq!(next, [10000; 4]);
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[compio_macros::test]
+async fn mpsc_coalesced() {
+    let (tx, mut rx) = mpsc::unbounded().unwrap();
+
+    let wakes = Arc::new(AtomicUsize::new(0));
+    {
+        let mut burst = pin!(counted(
+            async {
+                for i in 0..1000 {
+                    assert_eq!(rx.recv().await, Some(i));
+                }
+            },
+            wakes.clone(),
+        ));
+        // Start waiting before the burst.
+        assert!(poll_fn(|cx| Poll::Ready(burst.as_mut().poll(cx).is_pending())).await);
+        // Block the runtime thread, so that the receiver cannot wake up in the middle.
+        let sender = tx.clone();
+        thread::spawn(move || {
+            for i in 0..1000 {
+                sender.send(i).unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+        // The receiver is notified once, and the flag stays set until it wakes up.
+        assert_eq!(format!("{tx:?}"), "Sender { notified: true, .. }");
+        burst.await;
+    }
+    // The whole burst costs a single wake-up.
+    assert_eq!(wakes.load(Ordering::Relaxed), 1);
+
+    // No stale notification is left to wake the receiver spuriously.
+    let wakes = Arc::new(AtomicUsize::new(0));
+    let mut recv = pin!(counted(rx.recv(), wakes.clone()));
+    assert!(poll_fn(|cx| Poll::Ready(recv.as_mut().poll(cx).is_pending())).await);
+    // The flag is reset before the receiver waits again.
+    assert_eq!(format!("{tx:?}"), "Sender { notified: false, .. }");
+    for _ in 0..3 {
+        compio::runtime::yield_now().await;
+    }
+    assert_eq!(wakes.load(Ordering::Relaxed), 0);
+    let thread = thread::spawn(move || tx.send(1000).unwrap());
+    assert_eq!(recv.await, Some(1000));
+    assert_eq!(wakes.load(Ordering::Relaxed), 1);
+    thread.join().unwrap();
+}
+
+#[compio_macros::test]
+async fn mpsc_wait() {
+    let (tx, mut rx) = mpsc::unbounded().unwrap();
+    let thread = thread::spawn(move || {
+        for i in 0..3 {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(i).unwrap();
+        }
+    });
+    assert_eq!(rx.recv().await, Some(0));
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(rx.recv().await, Some(2));
+    assert_eq!(rx.recv().await, None);
+    thread.join().unwrap();
+}
+
+#[compio_macros::test]
+async fn mpsc_closed() {
+    let (tx, mut rx) = mpsc::unbounded().unwrap();
+    tx.send(1).unwrap();
+    rx.close();
+    assert!(tx.is_closed());
+    assert_eq!(tx.send(2).unwrap_err().0, 2);
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(rx.recv().await, None);
+
+    let (tx, rx) = mpsc::unbounded::<i32>().unwrap();
+    drop(rx);
+    assert!(thread::spawn(move || tx.send(1)).join().unwrap().is_err());
+}
+
+#[compio_macros::test]
+async fn oneshot() {
+    let (tx, rx) = oneshot::channel().unwrap();
+    let thread = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        tx.send(42).unwrap();
+    });
+    assert_eq!(rx.recv().await, Ok(42));
+    thread.join().unwrap();
+
+    let (tx, rx) = oneshot::channel::<i32>().unwrap();
+    thread::spawn(move || drop(tx));
+    assert!(rx.recv().await.is_err());
+
+    let (tx, rx) = oneshot::channel().unwrap();
+    drop(rx);
+    assert_eq!(tx.send(1), Err(1));
+}
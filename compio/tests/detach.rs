@@ -0,0 +1,104 @@
+use std::{future::Future, io::Write};
+
+use compio::{
+    buf::IntoInner,
+    fs::File,
+    io::{AsyncReadAtExt, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, UdpSocket},
+    runtime::{Attachable, Detached},
+};
+
+// Run the future on a new thread with its own runtime.
+fn on_thread<F: Future + 'static>(f: impl (FnOnce() -> F) + Send + 'static) -> F::Output
+where
+    F::Output: Send + 'static,
+{
+    std::thread::spawn(move || compio::runtime::block_on(f()))
+        .join()
+        .unwrap()
+}
+
+#[compio_macros::test]
+async fn tcp_stream() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut client, (mut server, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    // Use it on the current thread before moving it.
+    client.write_all("ping").await.0.unwrap();
+    let (_, buf) = server.read_exact(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(buf, b"ping");
+    assert!(server.is_attached());
+
+    let server = Detached::new(server).unwrap();
+    let echo = std::thread::spawn(move || {
+        compio::runtime::block_on(async move {
+            let mut server = server.attach().unwrap();
+            let (_, buf) = server.read_exact(Vec::with_capacity(4)).await.unwrap();
+            server.write_all(buf).await.0.unwrap();
+        })
+    });
+    client.write_all("pong").await.0.unwrap();
+    let (_, buf) = client.read_exact(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(buf, b"pong");
+    echo.join().unwrap();
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn unix_stream() {
+    use compio::net::{UnixListener, UnixStream};
+
+    let dir = tempfile::Builder::new()
+        .prefix("compio-uds-tests")
+        .tempdir()
+        .unwrap();
+    let sock_path = dir.path().join("detach.sock");
+    let listener = UnixListener::bind(&sock_path).unwrap();
+    let mut client = UnixStream::connect(&sock_path).unwrap();
+    let (server, _) = listener.accept().await.unwrap();
+
+    let server = Detached::new(server).unwrap();
+    client.write_all("hello").await.0.unwrap();
+    let buf = on_thread(move || async move {
+        let mut server = server.into_inner();
+        let (_, buf) = server.read_exact(Vec::with_capacity(5)).await.unwrap();
+        buf
+    });
+    assert_eq!(buf, b"hello");
+}
+
+#[compio_macros::test]
+async fn udp_socket() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = receiver.local_addr().unwrap();
+    let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    sender.send_to("first", &addr).await.0.unwrap();
+    let (_, buf) = receiver.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(buf, b"first");
+
+    let receiver = Detached::new(receiver).unwrap();
+    sender.send_to("second", &addr).await.0.unwrap();
+    let buf = on_thread(move || async move {
+        let receiver = receiver.attach().unwrap();
+        receiver.recv(Vec::with_capacity(16)).await.unwrap().1
+    });
+    assert_eq!(buf, b"second");
+}
+
+#[compio_macros::test]
+async fn file() {
+    let mut tempfile = tempfile::NamedTempFile::new().unwrap();
+    tempfile.write_all(b"hello").unwrap();
+    let file = File::open(tempfile.path()).unwrap();
+    let (_, buf) = file.read_to_end_at(vec![], 0).await.unwrap();
+    assert_eq!(buf, b"hello");
+
+    let file = Detached::new(file).unwrap();
+    let buf = on_thread(move || async move {
+        let file = file.attach().unwrap();
+        file.read_to_end_at(vec![], 0).await.unwrap().1
+    });
+    assert_eq!(buf, b"hello");
+}
@@ -1,13 +1,21 @@
-use std::num::NonZeroUsize;
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use compio::{
     buf::{arrayvec::ArrayVec, IntoInner},
-    dispatcher::Dispatcher,
+    dispatcher::{Dispatcher, JoinHandle, WorkerMetrics},
     io::{AsyncReadExt, AsyncWriteExt},
     net::{TcpListener, TcpStream},
     runtime::{spawn, Unattached},
     BufResult,
 };
+use futures_channel::oneshot;
 use futures_util::{stream::FuturesUnordered, StreamExt};
 
 #[compio_macros::test]
@@ -28,23 +36,534 @@ async fn listener_dispatch() {
         }));
         while let Some(()) = futures.next().await {}
     });
+    let mut handles = FuturesUnordered::new();
     for _i in 0..CLIENT_NUM {
         let (srv, _) = listener.accept().await.unwrap();
         let srv = Unattached::new(srv).unwrap();
-        dispatcher
+        let handle = dispatcher
             .dispatch(move || {
                 let mut srv = srv.into_inner();
                 async move {
                     let BufResult(res, buf) = srv.read_exact(ArrayVec::<u8, 12>::new()).await;
-                    res?;
+                    res.unwrap();
                     assert_eq!(buf.as_slice(), b"Hello world!");
-                    Ok(())
                 }
             })
             .unwrap();
+        handles.push(handle);
+    }
+    while let Some(res) = handles.next().await {
+        res.unwrap();
+    }
+    task.await.unwrap();
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[test]
+fn dispatch_result() {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .build()
+        .unwrap();
+    let handles = (0..4)
+        .map(|i| {
+            dispatcher
+                .dispatch(move || async move {
+                    if i == 2 {
+                        panic!("task {i} panicked");
+                    }
+                    std::thread::current().id()
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    // Await the handles on a thread without compio runtime.
+    let results = std::thread::spawn(move || {
+        handles
+            .into_iter()
+            .map(futures_executor::block_on)
+            .collect::<Vec<_>>()
+    })
+    .join()
+    .unwrap();
+    let main_thread = std::thread::current().id();
+    for (i, res) in results.into_iter().enumerate() {
+        if i == 2 {
+            assert!(res.unwrap_err().is_panic());
+        } else {
+            assert_ne!(res.unwrap(), main_thread);
+        }
+    }
+    // The panic is not propagated to the dispatcher.
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[test]
+fn dispatch_concurrent() {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    let (tx, rx) = oneshot::channel();
+    // The first task waits for the second one on the same worker.
+    let first = dispatcher.dispatch(move || rx).unwrap();
+    let second = dispatcher
+        .dispatch(move || async move { tx.send(42).unwrap() })
+        .unwrap();
+    futures_executor::block_on(second).unwrap();
+    assert_eq!(futures_executor::block_on(first).unwrap(), Ok(42));
+
+    // The error of a task is delivered, and the worker keeps running.
+    let failed = dispatcher
+        .dispatch(|| async { Err::<(), _>(std::io::Error::other("failed")) })
+        .unwrap();
+    assert!(futures_executor::block_on(failed).unwrap().is_err());
+    let res = dispatcher.dispatch(|| async { 1 }).unwrap();
+    assert_eq!(futures_executor::block_on(res).unwrap(), 1);
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[test]
+fn dispatch_max_concurrent_tasks() {
+    const LIMIT: usize = 2;
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .max_concurrent_tasks(NonZeroUsize::new(LIMIT).unwrap())
+        .build()
+        .unwrap();
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let handles = (0..10)
+        .map(|_| {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            dispatcher
+                .dispatch(move || async move {
+                    let n = running.fetch_add(1, Ordering::Relaxed) + 1;
+                    max_running.fetch_max(n, Ordering::Relaxed);
+                    for _ in 0..5 {
+                        compio::runtime::yield_now().await;
+                    }
+                    running.fetch_sub(1, Ordering::Relaxed);
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        futures_executor::block_on(handle).unwrap();
+    }
+    assert_eq!(max_running.load(Ordering::Relaxed), LIMIT);
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[test]
+fn dispatch_max_concurrent_tasks_unlimited() {
+    // Larger than the max permits of the semaphore.
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .max_concurrent_tasks(NonZeroUsize::MAX)
+        .build()
+        .unwrap();
+    let handle = dispatcher.dispatch(|| async { 42 }).unwrap();
+    assert_eq!(futures_executor::block_on(handle).unwrap(), 42);
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn dispatch_core_affinity() {
+    use std::collections::HashSet;
+
+    use compio::dispatcher::{worker_index, CoreAffinity};
+
+    const THREAD_NUM: usize = 3;
+
+    for affinity in [CoreAffinity::Cores, CoreAffinity::NumaNodes] {
+        let dispatcher = Dispatcher::builder()
+            .worker_threads(NonZeroUsize::new(THREAD_NUM).unwrap())
+            .core_affinity(affinity)
+            .build()
+            .unwrap();
+        let handles = (0..THREAD_NUM * 4)
+            .map(|_| dispatcher.dispatch(|| async { worker_index() }).unwrap())
+            .collect::<Vec<_>>();
+        let indices = handles
+            .into_iter()
+            .map(|handle| futures_executor::block_on(handle).unwrap().unwrap())
+            .collect::<HashSet<_>>();
+        assert!(indices.iter().all(|index| *index < THREAD_NUM));
+        for res in dispatcher.join() {
+            res.unwrap();
+        }
+    }
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(1).unwrap())
+        .core_affinity_with(|_| vec![0])
+        .build()
+        .unwrap();
+    let handle = dispatcher.dispatch(|| async { worker_index() }).unwrap();
+    assert_eq!(futures_executor::block_on(handle).unwrap(), Some(0));
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+
+    // An invalid CPU set fails the building.
+    assert!(Dispatcher::builder()
+        .core_affinity_with(|_| vec![])
+        .build()
+        .is_err());
+    assert!(Dispatcher::builder()
+        .core_affinity_with(|_| vec![usize::MAX])
+        .build()
+        .is_err());
+}
+
+#[test]
+fn dispatch_targeted() {
+    use compio::dispatcher::worker_index;
+
+    const THREAD_NUM: usize = 4;
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(THREAD_NUM).unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(dispatcher.worker_threads(), THREAD_NUM);
+
+    for index in 0..THREAD_NUM {
+        let handle = dispatcher
+            .dispatch_to(index, || async { worker_index() })
+            .unwrap();
+        assert_eq!(futures_executor::block_on(handle).unwrap(), Some(index));
+    }
+    assert!(dispatcher.dispatch_to(THREAD_NUM, || async {}).is_err());
+
+    for key in 0..16 {
+        let indices = (0..4)
+            .map(|_| {
+                let handle = dispatcher
+                    .dispatch_by_key(&key, || async { worker_index() })
+                    .unwrap();
+                futures_executor::block_on(handle).unwrap().unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(indices.iter().all(|index| *index == indices[0]));
+    }
+
+    let handles = dispatcher.broadcast(|| async { worker_index() }).unwrap();
+    let indices = handles
+        .into_iter()
+        .map(|handle| futures_executor::block_on(handle).unwrap().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(indices, (0..THREAD_NUM).collect::<Vec<_>>());
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+// Wait until the metrics of the worker satisfy `f`. The metrics are updated
+// by the worker thread without notifying anyone.
+fn wait_until(dispatcher: &Dispatcher, index: usize, f: impl Fn(WorkerMetrics) -> bool) {
+    while !f(dispatcher.metrics()[index]) {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+// The handles of the tasks filling up a worker.
+type Handles = Vec<JoinHandle<Result<(), oneshot::Canceled>>>;
+
+// Build a dispatcher which runs one task at a time on each worker, with one
+// slot in the queue, and fill up the worker `index`: one task runs until the
+// returned sender is used, and one is queued.
+fn fill_worker(nthreads: usize, index: usize) -> (Dispatcher, oneshot::Sender<()>, Handles) {
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(nthreads).unwrap())
+        .max_concurrent_tasks(NonZeroUsize::new(1).unwrap())
+        .queue_capacity(NonZeroUsize::new(1).unwrap())
+        .build()
+        .unwrap();
+    let (tx, rx) = oneshot::channel::<()>();
+    let (started_tx, started_rx) = std::sync::mpsc::channel();
+    let mut handles = vec![dispatcher
+        .dispatch_to(index, move || {
+            started_tx.send(()).unwrap();
+            rx
+        })
+        .unwrap()];
+    started_rx.recv().unwrap();
+    // In the queue, not received until the running task completes.
+    handles.push(dispatcher.dispatch_to(index, || async { Ok(()) }).unwrap());
+    (dispatcher, tx, handles)
+}
+
+#[test]
+fn dispatch_backpressure() {
+    use std::{
+        future::Future,
+        io::ErrorKind,
+        pin::pin,
+        task::{Context, Poll},
+    };
+
+    use futures_util::task::noop_waker_ref;
+
+    let (dispatcher, tx, mut handles) = fill_worker(1, 0);
+    let err = dispatcher.dispatch(|| async {}).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(
+        dispatcher.metrics()[0],
+        WorkerMetrics {
+            queued: 1,
+            in_flight: 2,
+            capacity: Some(1),
+            dispatched: 2,
+            completed: 0,
+        }
+    );
+
+    let handle = {
+        let mut waiting = pin!(dispatcher.dispatch_wait(|| async { Ok(()) }));
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(matches!(waiting.as_mut().poll(&mut cx), Poll::Pending));
+        // The running task completes, and the queue has space.
+        tx.send(()).unwrap();
+        futures_executor::block_on(waiting).unwrap()
+    };
+    handles.push(handle);
+    for handle in handles {
+        futures_executor::block_on(handle).unwrap().unwrap();
+    }
+    wait_until(&dispatcher, 0, |m| m.completed == 3);
+    assert_eq!(dispatcher.metrics()[0].in_flight, 0);
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[test]
+fn broadcast_queue_full() {
+    use std::io::ErrorKind;
+
+    use compio::dispatcher::worker_index;
+
+    // Fill the queue of the second worker.
+    let (dispatcher, tx, handles) = fill_worker(2, 1);
+
+    let err = dispatcher
+        .broadcast(|| async { worker_index() })
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    // Nothing is sent to the first worker.
+    assert_eq!(
+        dispatcher.metrics()[0],
+        WorkerMetrics {
+            queued: 0,
+            in_flight: 0,
+            capacity: Some(1),
+            dispatched: 0,
+            completed: 0,
+        }
+    );
+    assert_eq!(dispatcher.metrics()[1].in_flight, 2);
+
+    tx.send(()).unwrap();
+    for handle in handles {
+        futures_executor::block_on(handle).unwrap().unwrap();
+    }
+    wait_until(&dispatcher, 1, |m| m.in_flight == 0);
+    let handles = dispatcher.broadcast(|| async { worker_index() }).unwrap();
+    let indices = handles
+        .into_iter()
+        .map(|handle| futures_executor::block_on(handle).unwrap().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(indices, [0, 1]);
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[test]
+fn dispatch_policy() {
+    use compio::dispatcher::{worker_index, DispatchPolicy};
+
+    const THREAD_NUM: usize = 3;
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(THREAD_NUM).unwrap())
+        .dispatch_policy(DispatchPolicy::RoundRobin)
+        .build()
+        .unwrap();
+    let indices = (0..THREAD_NUM * 2)
+        .map(|_| {
+            let handle = dispatcher.dispatch(|| async { worker_index() }).unwrap();
+            futures_executor::block_on(handle).unwrap().unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(indices, [0, 1, 2, 0, 1, 2]);
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(THREAD_NUM).unwrap())
+        .dispatch_policy(DispatchPolicy::PowerOfTwoChoices)
+        .build()
+        .unwrap();
+    let handles = (0..THREAD_NUM * 4)
+        .map(|_| dispatcher.dispatch(|| async { worker_index() }).unwrap())
+        .collect::<Vec<_>>();
+    for handle in handles {
+        assert!(futures_executor::block_on(handle).unwrap().unwrap() < THREAD_NUM);
+    }
+    let dispatched = dispatcher
+        .metrics()
+        .iter()
+        .map(|m| m.dispatched)
+        .sum::<u64>();
+    assert_eq!(dispatched, THREAD_NUM as u64 * 4);
+    for res in dispatcher.join() {
+        res.unwrap();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn serve_tcp() {
+    use std::sync::mpsc;
+
+    use compio::dispatcher::worker_index;
+
+    const THREAD_NUM: usize = 2;
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(THREAD_NUM).unwrap())
+        .build()
+        .unwrap();
+    fn assert_send<T: Send>(t: T) -> T {
+        t
+    }
+
+    // Signal when a connection is accepted, and when it is served.
+    let (accepted_tx, accepted_rx) = mpsc::channel();
+    let (served_tx, served_rx) = mpsc::channel();
+    let server = assert_send(dispatcher.serve_tcp(
+        "127.0.0.1:0".parse().unwrap(),
+        move |mut stream, _| {
+            accepted_tx.send(()).unwrap();
+            let served_tx = served_tx.clone();
+            async move {
+                let (_, buf) = stream.read_exact(Vec::with_capacity(4)).await.unwrap();
+                stream.write_all(buf).await.unwrap();
+                let index = worker_index().unwrap() as u8;
+                stream.write_all(vec![index]).await.unwrap();
+                served_tx.send(()).unwrap();
+            }
+        },
+    ))
+    .await
+    .unwrap();
+    let addr = server.local_addr();
+    assert_ne!(addr.port(), 0);
+
+    for _ in 0..8 {
+        let mut client = TcpStream::connect(&addr).await.unwrap();
+        client.write_all("ping").await.unwrap();
+        let (_, buf) = client.read_exact(Vec::with_capacity(5)).await.unwrap();
+        assert_eq!(&buf[..4], b"ping");
+        assert!((buf[4] as usize) < THREAD_NUM);
+        accepted_rx.recv().unwrap();
+        served_rx.recv().unwrap();
     }
-    task.await;
+
+    // The accepted connection is served after shutting down.
+    let mut client = TcpStream::connect(&addr).await.unwrap();
+    client.write_all("pi").await.unwrap();
+    accepted_rx.recv().unwrap();
+    let (res, buf) = futures_util::join!(server.shutdown(), async {
+        // The server is stopped when the shutdown future is polled the first time.
+        client.write_all("ng").await.unwrap();
+        client.read_exact(Vec::with_capacity(5)).await.unwrap().1
+    });
+    res.unwrap();
+    assert_eq!(&buf[..4], b"ping");
+    // The shutdown waits for the connection to be served.
+    served_rx.try_recv().unwrap();
+    assert!(TcpStream::connect(&addr).await.is_err());
     for res in dispatcher.join() {
         res.unwrap();
     }
 }
+
+#[test]
+fn shutdown_graceful() {
+    use std::time::Instant;
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .build()
+        .unwrap();
+    let handles = (0..4)
+        .map(|i| {
+            dispatcher
+                .dispatch(move || async move {
+                    compio::runtime::yield_now().await;
+                    i
+                })
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let reports = dispatcher.shutdown(Instant::now() + Duration::from_secs(10));
+    assert_eq!(reports.len(), 2);
+    for report in reports {
+        let report = report.unwrap();
+        assert!(report.graceful);
+        assert_eq!(report.runtime.cancelled_tasks, 0);
+    }
+    for (i, handle) in handles.into_iter().enumerate() {
+        assert_eq!(futures_executor::block_on(handle).unwrap(), i);
+    }
+}
+
+#[compio_macros::test]
+async fn shutdown_deadline() {
+    use std::time::Instant;
+
+    let dispatcher = Dispatcher::builder()
+        .worker_threads(NonZeroUsize::new(2).unwrap())
+        .cancel_timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+    // Pending on an IO operation.
+    let accepting = dispatcher
+        .dispatch_to(0, || async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.accept().await.map(|_| ())
+        })
+        .unwrap();
+    let completed = dispatcher.dispatch_to(1, || async { 1 }).unwrap();
+    assert_eq!(completed.await.unwrap(), 1);
+
+    let reports = dispatcher
+        .shutdown_async(Instant::now() + Duration::from_millis(50))
+        .await;
+    let report = reports[0].as_ref().unwrap();
+    assert!(!report.graceful);
+    assert!(report.runtime.cancelled_tasks >= 1);
+    assert_eq!(report.runtime.cancelled_ops, 1);
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    assert_eq!(report.runtime.leaked_ops, 0);
+    assert!(reports[1].as_ref().unwrap().graceful);
+    assert!(accepting.await.unwrap_err().is_cancelled());
+}
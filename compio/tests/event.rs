@@ -1,4 +1,15 @@
-use compio::event::Event;
+use std::{
+    cell::Cell,
+    future::{poll_fn, Future},
+    pin::pin,
+    rc::Rc,
+    task::Poll,
+};
+
+use compio::{
+    event::{Event, EventMode},
+    runtime::yield_now,
+};
 
 #[compio_macros::test]
 async fn event_handle() {
@@ -9,3 +20,123 @@ async fn event_handle() {
     });
     event.wait().await.unwrap();
 }
+
+#[compio_macros::test]
+async fn event_wait_many_times() {
+    let event = Event::new().unwrap();
+    let handle = event.handle().unwrap();
+    for _ in 0..3 {
+        handle.notify().unwrap();
+        event.wait().await.unwrap();
+    }
+    // Notify when the read from the driver is pending.
+    for _ in 0..3 {
+        let (res, ()) = futures_util::join!(event.wait(), async {
+            yield_now().await;
+            handle.notify().unwrap();
+        });
+        res.unwrap();
+    }
+    assert!(!event.is_set());
+}
+
+// Spawn the waiters, and return the counter of the woken ones.
+fn spawn_waiters(event: &Rc<Event>, n: usize) -> Rc<Cell<usize>> {
+    let woken = Rc::new(Cell::new(0));
+    for _ in 0..n {
+        let event = event.clone();
+        let woken = woken.clone();
+        compio::runtime::spawn(async move {
+            event.wait().await.unwrap();
+            woken.set(woken.get() + 1);
+        })
+        .detach();
+    }
+    woken
+}
+
+async fn wait_woken(woken: &Cell<usize>, n: usize) {
+    while woken.get() < n {
+        yield_now().await;
+    }
+    assert_eq!(woken.get(), n);
+}
+
+#[compio_macros::test]
+async fn event_auto_reset() {
+    let event = Rc::new(Event::new().unwrap());
+    let handle = event.handle().unwrap();
+    let woken = spawn_waiters(&event, 3);
+    yield_now().await;
+    // Every notification wakes a single waiter.
+    for i in 1..=3 {
+        handle.notify().unwrap();
+        wait_woken(&woken, i).await;
+    }
+
+    // The notifications are coalesced.
+    for _ in 0..3 {
+        handle.notify().unwrap();
+    }
+    event.wait().await.unwrap();
+    assert!(!event.is_set());
+}
+
+#[compio_macros::test]
+async fn event_manual_reset() {
+    let event = Rc::new(Event::with_mode(EventMode::ManualReset).unwrap());
+    let handle = event.handle().unwrap();
+    let woken = spawn_waiters(&event, 3);
+    yield_now().await;
+    handle.notify().unwrap();
+    wait_woken(&woken, 3).await;
+
+    assert!(event.is_set());
+    event.wait().await.unwrap();
+    event.reset();
+    assert!(!event.is_set());
+}
+
+#[compio_macros::test]
+async fn event_counting() {
+    let event = Event::with_mode(EventMode::Counting).unwrap();
+    let handle = event.handle().unwrap();
+    std::thread::spawn(move || {
+        for _ in 0..5 {
+            handle.notify().unwrap();
+        }
+    })
+    .join()
+    .unwrap();
+    event.wait().await.unwrap();
+    assert!(event.is_set());
+    assert_eq!(event.wait_count().await.unwrap(), 4);
+    assert!(!event.is_set());
+}
+
+#[compio_macros::test]
+async fn event_cancel_reading() {
+    let event = Event::with_mode(EventMode::Counting).unwrap();
+    let handle = event.handle().unwrap();
+    {
+        let mut wait = pin!(event.wait_count());
+        // Start reading from the driver.
+        assert!(poll_fn(|cx| Poll::Ready(wait.as_mut().poll(cx).is_pending())).await);
+        for _ in 0..3 {
+            handle.notify().unwrap();
+        }
+        // Let the read complete in the driver, but never observe it.
+        for _ in 0..3 {
+            yield_now().await;
+        }
+    }
+    handle.notify().unwrap();
+    // The notifications consumed by the dropped waiter are not lost. The new one
+    // may be received together with them, or after them.
+    let mut count = event.wait_count().await.unwrap();
+    assert!(count >= 3);
+    if count < 4 {
+        count += event.wait_count().await.unwrap();
+    }
+    assert_eq!(count, 4);
+}
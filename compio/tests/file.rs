@@ -1,8 +1,8 @@
 use std::io::prelude::*;
 
 use compio::{
-    fs::File,
-    io::{AsyncReadAtExt, AsyncWriteAtExt},
+    fs::{File, OpenOptions},
+    io::{AsyncReadAtExt, AsyncWriteAt, AsyncWriteAtExt},
 };
 use tempfile::NamedTempFile;
 
@@ -65,6 +65,139 @@ async fn drop_open() {
     assert_eq!(file, HELLO);
 }
 
+#[compio_macros::test]
+async fn append() {
+    let mut tempfile = tempfile();
+    tempfile.write_all(b"hello").unwrap();
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(tempfile.path())
+        .unwrap();
+    let (n, _) = file.append(" world").await.unwrap();
+    assert_eq!(n, 6);
+    // The position is ignored in the append mode.
+    file.write_all_at("...", 0).await.unwrap();
+
+    let file = std::fs::read(tempfile.path()).unwrap();
+    assert_eq!(file, HELLO);
+}
+
+#[compio_macros::test]
+async fn append_not_append_mode() {
+    let tempfile = tempfile();
+
+    let file = File::create(tempfile.path()).unwrap();
+    let err = file.append(HELLO).await.0.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn open_mode_and_flags() {
+    use std::os::unix::fs::PermissionsExt;
+
+    use compio::driver::AsRawFd;
+    use nix::fcntl::{fcntl, FcntlArg, OFlag};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("file");
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .sync(true)
+        .open(&path)
+        .unwrap();
+    let mode = file.metadata().unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    let flags = OFlag::from_bits_retain(fcntl(file.as_raw_fd(), FcntlArg::F_GETFL).unwrap());
+    assert!(flags.contains(OFlag::O_DSYNC));
+
+    // The custom flags are combined with the others.
+    let link = dir.path().join("link");
+    std::os::unix::fs::symlink(&path, &link).unwrap();
+    let err = OpenOptions::new()
+        .read(true)
+        .custom_flags(nix::libc::O_NOFOLLOW)
+        .open(&link)
+        .unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(nix::libc::ELOOP));
+}
+
+// Open the file for direct IO, or return `None` if the filesystem doesn't
+// support it.
+#[cfg(target_os = "linux")]
+fn open_direct_file(options: OpenOptions, path: impl AsRef<std::path::Path>) -> Option<File> {
+    match options.direct(true).open(path) {
+        Ok(file) => Some(file),
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidInput => {
+            eprintln!("skipped: the filesystem doesn't support direct IO: {e}");
+            None
+        }
+        Err(e) => panic!("{e}"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn open_direct() {
+    use compio::driver::AsRawFd;
+    use nix::fcntl::{fcntl, FcntlArg, OFlag};
+
+    let dir = tempfile::tempdir_in(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let options = OpenOptions::new().write(true).create(true);
+    let Some(file) = open_direct_file(options, dir.path().join("file")) else {
+        return;
+    };
+    let flags = OFlag::from_bits_retain(fcntl(file.as_raw_fd(), FcntlArg::F_GETFL).unwrap());
+    assert!(flags.contains(OFlag::O_DIRECT));
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn direct_io_aligned_buf() {
+    use compio::buf::{AlignedBuf, IoBuf};
+
+    let dir = tempfile::tempdir_in(env!("CARGO_TARGET_TMPDIR")).unwrap();
+    let path = dir.path().join("file");
+    let options = OpenOptions::new().read(true).write(true).create(true);
+    let Some(file) = open_direct_file(options, &path) else {
+        return;
+    };
+    let align = file.direct_io_alignment().unwrap();
+    assert!(align.is_power_of_two());
+
+    let mut buf = AlignedBuf::with_capacity(align, align);
+    buf.extend_from_slice(HELLO);
+    buf.resize(align, 0);
+    (&file).write_all_at(buf, align as u64).await.unwrap();
+
+    let buf = AlignedBuf::with_capacity(align * 2, align);
+    let (n, buf) = file.read_exact_at(buf, 0).await.unwrap();
+    assert_eq!(n, align * 2);
+    assert!(buf[..align].iter().all(|b| *b == 0));
+    assert_eq!(&buf[align..align + HELLO.len()], HELLO);
+
+    // Misaligned buffers are rejected in debug builds.
+    if cfg!(debug_assertions) {
+        let err = (&file)
+            .write_at(AlignedBuf::zeroed(align, align), 1)
+            .await
+            .0
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let err = (&file)
+            .write_at(AlignedBuf::zeroed(1, align).slice(1..), 0)
+            .await
+            .0
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("misaligned buffer"));
+    }
+}
+
 fn tempfile() -> NamedTempFile {
     NamedTempFile::new().unwrap()
 }
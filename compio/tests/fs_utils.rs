@@ -0,0 +1,158 @@
+use std::io;
+
+use compio::fs;
+use tempfile::TempDir;
+
+const HELLO: &[u8] = b"hello world...";
+
+fn tempdir() -> TempDir {
+    tempfile::tempdir().unwrap()
+}
+
+#[compio_macros::test]
+async fn read_write() {
+    let dir = tempdir();
+    let path = dir.path().join("hello.txt");
+
+    let ((), buffer) = fs::write(&path, HELLO).await.unwrap();
+    assert_eq!(buffer, HELLO);
+
+    assert_eq!(fs::read(&path).await.unwrap(), HELLO);
+    assert_eq!(fs::read_to_string(&path).await.unwrap().as_bytes(), HELLO);
+
+    // Truncate the previous contents.
+    fs::write(&path, "hi").await.unwrap();
+    assert_eq!(fs::read_to_string(&path).await.unwrap(), "hi");
+}
+
+#[compio_macros::test]
+async fn read_to_string_invalid_utf8() {
+    let dir = tempdir();
+    let path = dir.path().join("invalid.bin");
+    fs::write(&path, vec![0xffu8, 0xfe]).await.unwrap();
+
+    let err = fs::read_to_string(&path).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[compio_macros::test]
+async fn read_not_found() {
+    let dir = tempdir();
+    let err = fs::read(dir.path().join("missing")).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[compio_macros::test]
+async fn copy_rename_remove() {
+    let dir = tempdir();
+    let from = dir.path().join("from.txt");
+    let to = dir.path().join("to.txt");
+    let renamed = dir.path().join("renamed.txt");
+
+    fs::write(&from, HELLO).await.unwrap();
+    assert_eq!(fs::copy(&from, &to).await.unwrap(), HELLO.len() as u64);
+    assert_eq!(fs::read(&to).await.unwrap(), HELLO);
+
+    fs::rename(&to, &renamed).await.unwrap();
+    assert!(!to.exists());
+    assert_eq!(fs::read(&renamed).await.unwrap(), HELLO);
+
+    fs::remove_file(&renamed).await.unwrap();
+    assert!(!renamed.exists());
+    let err = fs::remove_file(&renamed).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[compio_macros::test]
+async fn create_remove_dir() {
+    let dir = tempdir();
+    let nested = dir.path().join("a").join("b").join("c");
+
+    fs::create_dir_all(&nested).await.unwrap();
+    assert!(fs::metadata(&nested).await.unwrap().is_dir());
+    fs::write(nested.join("file"), HELLO).await.unwrap();
+
+    fs::remove_dir(&nested).await.unwrap_err();
+    fs::remove_file(nested.join("file")).await.unwrap();
+    fs::remove_dir(&nested).await.unwrap();
+    assert!(!nested.exists());
+
+    fs::remove_dir_all(dir.path().join("a")).await.unwrap();
+    assert!(!dir.path().join("a").exists());
+}
+
+#[compio_macros::test]
+async fn create_dir_exists() {
+    let dir = tempdir();
+    let path = dir.path().join("dir");
+
+    fs::create_dir(&path).await.unwrap();
+    let err = fs::create_dir(&path).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    let err = fs::create_dir(path.join("a").join("b")).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    // Existing directories are fine, but not the files.
+    fs::create_dir_all(&path).await.unwrap();
+    fs::create_dir_all(dir.path()).await.unwrap();
+    fs::create_dir_all("").await.unwrap();
+    fs::write(path.join("file"), HELLO).await.unwrap();
+    fs::create_dir_all(path.join("file")).await.unwrap_err();
+    fs::create_dir_all(path.join("file").join("a")).await.unwrap_err();
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn nul_in_path() {
+    let err = fs::remove_file("hello\0world").await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = fs::rename("hello", "hello\0world").await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[compio_macros::test]
+async fn links_and_metadata() {
+    let dir = tempdir();
+    let original = dir.path().join("original.txt");
+    let hard = dir.path().join("hard.txt");
+    fs::write(&original, HELLO).await.unwrap();
+
+    fs::hard_link(&original, &hard).await.unwrap();
+    assert_eq!(fs::read(&hard).await.unwrap(), HELLO);
+
+    let metadata = fs::metadata(&original).await.unwrap();
+    assert!(metadata.is_file());
+    assert_eq!(metadata.len(), HELLO.len() as u64);
+
+    let canonical = fs::canonicalize(&original).await.unwrap();
+    assert!(canonical.is_absolute());
+    assert_eq!(canonical, std::fs::canonicalize(&original).unwrap());
+
+    #[cfg(unix)]
+    {
+        let link = dir.path().join("link.txt");
+        fs::symlink(&original, &link).await.unwrap();
+        assert_eq!(fs::read(&link).await.unwrap(), HELLO);
+        assert!(fs::symlink_metadata(&link).await.unwrap().is_symlink());
+        assert!(fs::metadata(&link).await.unwrap().is_file());
+        assert_eq!(fs::canonicalize(&link).await.unwrap(), canonical);
+    }
+}
+
+#[compio_macros::test]
+async fn set_permissions() {
+    let dir = tempdir();
+    let path = dir.path().join("readonly.txt");
+    fs::write(&path, HELLO).await.unwrap();
+
+    let mut perm = fs::metadata(&path).await.unwrap().permissions();
+    perm.set_readonly(true);
+    fs::set_permissions(&path, perm).await.unwrap();
+    assert!(fs::metadata(&path).await.unwrap().permissions().readonly());
+
+    let mut perm = fs::metadata(&path).await.unwrap().permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    perm.set_readonly(false);
+    fs::set_permissions(&path, perm).await.unwrap();
+    assert!(!fs::metadata(&path).await.unwrap().permissions().readonly());
+}
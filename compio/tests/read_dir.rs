@@ -0,0 +1,113 @@
+use std::{collections::BTreeSet, io, path::PathBuf};
+
+use compio::fs;
+use futures_util::{StreamExt, TryStreamExt};
+
+#[compio_macros::test]
+async fn read_dir_batches() {
+    let dir = tempfile::tempdir().unwrap();
+    // More than one batch.
+    for i in 0..100 {
+        fs::write(dir.path().join(i.to_string()), i.to_string())
+            .await
+            .unwrap();
+    }
+    fs::create_dir_all(dir.path().join("sub")).await.unwrap();
+
+    let mut entries = fs::read_dir(dir.path()).await.unwrap();
+    let mut names = BTreeSet::new();
+    while let Some(entry) = entries.next_entry().await.unwrap() {
+        let name = entry.file_name().into_string().unwrap();
+        assert_eq!(entry.path(), dir.path().join(&name));
+        if name == "sub" {
+            assert!(entry.file_type().is_dir());
+        } else {
+            assert!(entry.file_type().is_file());
+            let metadata = entry.metadata().await.unwrap();
+            assert_eq!(metadata.len(), name.len() as u64);
+        }
+        assert!(names.insert(name));
+    }
+    assert_eq!(names.len(), 101);
+    // The stream is fused.
+    assert!(entries.next_entry().await.unwrap().is_none());
+}
+
+#[compio_macros::test]
+async fn read_dir_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let entries = fs::read_dir(dir.path()).await.unwrap();
+    assert_eq!(entries.count().await, 0);
+}
+
+#[compio_macros::test]
+async fn read_dir_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = fs::read_dir(dir.path().join("missing")).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+async fn walk(root: &std::path::Path, max_concurrent: usize) -> BTreeSet<PathBuf> {
+    fs::walk_dir(root, max_concurrent)
+        .map_ok(|entry| entry.path().strip_prefix(root).unwrap().to_path_buf())
+        .try_collect()
+        .await
+        .unwrap()
+}
+
+#[compio_macros::test]
+async fn walk_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let mut expected = BTreeSet::new();
+    for a in 0..3 {
+        for b in 0..3 {
+            let sub = PathBuf::from(a.to_string()).join(b.to_string());
+            fs::create_dir_all(root.join(&sub)).await.unwrap();
+            fs::write(root.join(&sub).join("file"), "hello")
+                .await
+                .unwrap();
+            expected.insert(PathBuf::from(a.to_string()));
+            expected.insert(sub.clone());
+            expected.insert(sub.join("file"));
+        }
+    }
+
+    for max_concurrent in [0, 1, 2, 16] {
+        assert_eq!(walk(root, max_concurrent).await, expected);
+    }
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn walk_dir_symlink() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("a")).await.unwrap();
+    fs::write(root.join("a").join("file"), "hello")
+        .await
+        .unwrap();
+    // A loop, which is not followed.
+    fs::symlink(root, root.join("a").join("link"))
+        .await
+        .unwrap();
+
+    let expected = ["a", "a/file", "a/link"]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(walk(root, 4).await, expected);
+}
+
+#[compio_macros::test]
+async fn walk_dir_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let results = fs::walk_dir(dir.path().join("missing"), 4)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0].as_ref().unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+}
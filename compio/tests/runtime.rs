@@ -1,14 +1,14 @@
 #![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 
-use std::net::Ipv4Addr;
+use std::{cell::Cell, future::poll_fn, net::Ipv4Addr, rc::Rc, task::Poll};
 
 use compio::{
     buf::*,
     fs::File,
     io::{AsyncReadAt, AsyncReadExt, AsyncWriteAt, AsyncWriteExt},
-    net::{TcpListener, TcpStream},
+    net::{TcpListener, TcpStream, UdpSocket},
 };
-use compio_runtime::Unattached;
+use compio_runtime::{RuntimeHandle, Unattached};
 use tempfile::NamedTempFile;
 
 #[compio_macros::test]
@@ -68,6 +68,114 @@ async fn try_clone() {
     }
 }
 
+#[compio_macros::test]
+async fn wake_from_other_thread() {
+    let (tx, rx) = futures_channel::oneshot::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        tx.send(42).unwrap();
+    });
+    assert_eq!(rx.await.unwrap(), 42);
+}
+
+#[compio_macros::test]
+async fn remote_spawn() {
+    let thread_id = std::thread::current().id();
+    let handle = RuntimeHandle::current();
+    let tasks = std::thread::spawn(move || {
+        (0..10)
+            .map(|i| {
+                handle.spawn(move || async move {
+                    assert_eq!(std::thread::current().id(), thread_id);
+                    i
+                })
+            })
+            .collect::<Vec<_>>()
+    })
+    .join()
+    .unwrap();
+    for (i, task) in tasks.into_iter().enumerate() {
+        assert_eq!(task.await.unwrap(), i);
+    }
+}
+
+#[compio_macros::test]
+async fn self_waking_task() {
+    const DATA: &str = "Hello world!";
+
+    let stop = Rc::new(Cell::new(false));
+    let spinner = compio_runtime::spawn({
+        let stop = stop.clone();
+        poll_fn(move |cx| {
+            if stop.get() {
+                Poll::Ready(())
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+    });
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    tx.write_all(DATA).await.0.unwrap();
+    let (_, buffer) = rx.read_exact(Vec::with_capacity(DATA.len())).await.unwrap();
+    assert_eq!(DATA, String::from_utf8(buffer).unwrap());
+
+    stop.set(true);
+    spinner.await.unwrap();
+}
+
+#[cfg(feature = "time")]
+#[compio_macros::test]
+async fn yield_now() {
+    let stop = Rc::new(Cell::new(false));
+    let spinner = compio_runtime::spawn({
+        let stop = stop.clone();
+        async move {
+            while !stop.get() {
+                compio_runtime::yield_now().await;
+            }
+        }
+    });
+
+    compio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+    stop.set(true);
+    spinner.await.unwrap();
+}
+
+#[test]
+fn shutdown() {
+    std::thread::spawn(|| {
+        compio_runtime::block_on(async {
+            let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+            compio_runtime::spawn(async move { socket.recv(Vec::with_capacity(8)).await }).detach();
+            compio_runtime::spawn(std::future::pending::<()>()).detach();
+            compio_runtime::yield_now().await;
+        });
+        // Never polled.
+        compio_runtime::spawn(async {}).detach();
+
+        let report = compio_runtime::shutdown(std::time::Duration::from_secs(1));
+        assert_eq!(report.cancelled_tasks, 3);
+        assert_eq!(report.cancelled_ops, 1);
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        assert_eq!(report.leaked_ops, 0);
+
+        // The runtime stays closed until it is reopened.
+        let res = compio_runtime::block_on(compio_runtime::spawn(async { 42 }));
+        assert!(res.unwrap_err().is_cancelled());
+        compio_runtime::reopen();
+        let res = compio_runtime::block_on(compio_runtime::spawn(async { 42 }));
+        assert_eq!(res.unwrap(), 42);
+    })
+    .join()
+    .unwrap();
+}
+
 #[compio_macros::test]
 async fn drop_on_complete() {
     use std::sync::Arc;
@@ -0,0 +1,283 @@
+use std::{
+    cell::RefCell,
+    future::{poll_fn, Future},
+    pin::{pin, Pin},
+    rc::Rc,
+    task::Poll,
+};
+
+use compio::{
+    runtime::{spawn, yield_now},
+    sync::{mpsc, oneshot, watch, Mutex, Notify, RwLock, Semaphore},
+};
+
+// Poll the future once, and return if it is ready.
+async fn poll_once<F: Future + Unpin>(future: &mut F) -> Option<F::Output> {
+    poll_fn(|cx| {
+        Poll::Ready(match Pin::new(&mut *future).poll(cx) {
+            Poll::Ready(output) => Some(output),
+            Poll::Pending => None,
+        })
+    })
+    .await
+}
+
+#[compio_macros::test]
+async fn mutex_fifo() {
+    let mutex = Rc::new(Mutex::new(vec![]));
+    let guard = mutex.lock().await;
+    let tasks = (0..5)
+        .map(|i| {
+            let mutex = mutex.clone();
+            spawn(async move {
+                let mut guard = mutex.lock().await;
+                // Hold the lock across an await point.
+                yield_now().await;
+                guard.push(i);
+            })
+        })
+        .collect::<Vec<_>>();
+    yield_now().await;
+    assert!(mutex.try_lock().is_none());
+    drop(guard);
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(*mutex.lock().await, [0, 1, 2, 3, 4]);
+}
+
+#[compio_macros::test]
+async fn mutex_cancelled_waiter() {
+    let mutex = Mutex::new(0);
+    let guard = mutex.lock().await;
+    let mut cancelled = Box::pin(mutex.lock());
+    assert!(poll_once(&mut cancelled).await.is_none());
+    let mut waiter = pin!(mutex.lock());
+    assert!(poll_once(&mut waiter).await.is_none());
+
+    drop(guard);
+    // The lock is assigned to the first waiter, and passed to the next one
+    // when it is cancelled.
+    assert!(mutex.try_lock().is_none());
+    drop(cancelled);
+    *poll_once(&mut waiter).await.unwrap() += 1;
+    assert_eq!(*mutex.lock().await, 1);
+}
+
+#[compio_macros::test]
+async fn rwlock_writer_blocks_readers() {
+    let lock = Rc::new(RwLock::new(0));
+    let order = Rc::new(RefCell::new(vec![]));
+    let reader = lock.read().await;
+
+    let writer = spawn({
+        let (lock, order) = (lock.clone(), order.clone());
+        async move {
+            *lock.write().await += 1;
+            order.borrow_mut().push("write");
+        }
+    });
+    yield_now().await;
+    // A new reader waits after the writer.
+    assert!(lock.try_read().is_none());
+    let late_reader = spawn({
+        let (lock, order) = (lock.clone(), order.clone());
+        async move {
+            assert_eq!(*lock.read().await, 1);
+            order.borrow_mut().push("read");
+        }
+    });
+    yield_now().await;
+    assert!(order.borrow().is_empty());
+
+    drop(reader);
+    writer.await.unwrap();
+    late_reader.await.unwrap();
+    assert_eq!(*order.borrow(), ["write", "read"]);
+}
+
+#[compio_macros::test]
+async fn semaphore_fair() {
+    let semaphore = Semaphore::new(1);
+    let permit = semaphore.acquire().await.unwrap();
+    let mut many = pin!(semaphore.acquire_many(2));
+    assert!(poll_once(&mut many).await.is_none());
+    let mut one = pin!(semaphore.acquire());
+    assert!(poll_once(&mut one).await.is_none());
+
+    // The large acquire is queued first, so the small one waits.
+    drop(permit);
+    assert!(poll_once(&mut one).await.is_none());
+    semaphore.add_permits(2);
+    let many = poll_once(&mut many).await.unwrap().unwrap();
+    assert_eq!(many.num_permits(), 2);
+    let one = poll_once(&mut one).await.unwrap().unwrap();
+    assert_eq!(semaphore.available_permits(), 0);
+    drop((many, one));
+    assert_eq!(semaphore.available_permits(), 3);
+
+    semaphore.close();
+    assert!(semaphore.acquire().await.is_err());
+}
+
+#[compio_macros::test]
+async fn notify_one_permit() {
+    let notify = Notify::new();
+    notify.notify_one();
+    notify.notify_one();
+    // Only one permit is stored.
+    notify.notified().await;
+    let mut notified = pin!(notify.notified());
+    assert!(poll_once(&mut notified).await.is_none());
+    notify.notify_one();
+    assert!(poll_once(&mut notified).await.is_some());
+}
+
+#[compio_macros::test]
+async fn notify_waiters() {
+    let notify = Notify::new();
+    let mut a = pin!(notify.notified());
+    let mut b = pin!(notify.notified());
+    assert!(poll_once(&mut a).await.is_none());
+    // Not polled yet, but created before the notification.
+    notify.notify_waiters();
+    assert!(poll_once(&mut a).await.is_some());
+    assert!(poll_once(&mut b).await.is_some());
+
+    // No permit is stored.
+    let mut c = pin!(notify.notified());
+    assert!(poll_once(&mut c).await.is_none());
+}
+
+#[compio_macros::test]
+async fn notify_forward_cancelled() {
+    let notify = Notify::new();
+    let mut a = Box::pin(notify.notified());
+    let mut b = pin!(notify.notified());
+    assert!(poll_once(&mut a).await.is_none());
+    assert!(poll_once(&mut b).await.is_none());
+    notify.notify_one();
+    drop(a);
+    assert!(poll_once(&mut b).await.is_some());
+}
+
+#[compio_macros::test]
+async fn oneshot() {
+    let (tx, rx) = oneshot::channel();
+    tx.send(1).unwrap();
+    assert_eq!(rx.await, Ok(1));
+
+    let (tx, rx) = oneshot::channel::<i32>();
+    drop(tx);
+    assert!(rx.await.is_err());
+
+    let (mut tx, rx) = oneshot::channel();
+    let task = spawn(async move {
+        tx.closed().await;
+        tx.send(1)
+    });
+    yield_now().await;
+    drop(rx);
+    assert_eq!(task.await.unwrap(), Err(1));
+}
+
+#[compio_macros::test]
+async fn mpsc_bounded_backpressure() {
+    let (tx, mut rx) = mpsc::channel(2);
+    let sent = Rc::new(RefCell::new(0));
+    let task = spawn({
+        let sent = sent.clone();
+        async move {
+            for i in 0..5 {
+                tx.send(i).await.unwrap();
+                *sent.borrow_mut() += 1;
+            }
+        }
+    });
+    yield_now().await;
+    assert_eq!(*sent.borrow(), 2);
+    assert_eq!(rx.recv().await, Some(0));
+    yield_now().await;
+    assert_eq!(*sent.borrow(), 3);
+
+    let mut values = vec![];
+    while let Some(i) = rx.recv().await {
+        values.push(i);
+    }
+    assert_eq!(values, [1, 2, 3, 4]);
+    task.await.unwrap();
+}
+
+#[compio_macros::test]
+async fn mpsc_close() {
+    let (tx, mut rx) = mpsc::channel(1);
+    tx.try_send(1).unwrap();
+    assert!(matches!(tx.try_send(2), Err(mpsc::TrySendError::Full(2))));
+    let blocked = spawn({
+        let tx = tx.clone();
+        async move { tx.send(3).await }
+    });
+    yield_now().await;
+
+    rx.close();
+    assert!(tx.is_closed());
+    // The pending sender fails, but the sent value is still received.
+    assert_eq!(blocked.await.unwrap().unwrap_err().0, 3);
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(rx.recv().await, None);
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    for i in 0..100 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+    let mut count = 0;
+    while rx.recv().await.is_some() {
+        count += 1;
+    }
+    assert_eq!(count, 100);
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+}
+
+#[compio_macros::test]
+async fn watch_latest() {
+    let (tx, mut rx) = watch::channel(0);
+    for i in 1..=3 {
+        tx.send(i).unwrap();
+    }
+    // A new receiver has seen the current value.
+    let mut rx2 = tx.subscribe();
+    // The values are coalesced.
+    rx.changed().await.unwrap();
+    assert_eq!(*rx.borrow_and_update(), 3);
+    assert!(!rx.has_changed().unwrap());
+
+    let task = spawn(async move {
+        rx2.changed().await.unwrap();
+        let value = *rx2.borrow_and_update();
+        assert!(rx2.changed().await.is_err());
+        value
+    });
+    yield_now().await;
+    tx.send_modify(|v| *v += 1);
+    yield_now().await;
+    drop(tx);
+    assert_eq!(task.await.unwrap(), 4);
+    assert!(rx.has_changed().is_err());
+}
+
+#[compio_macros::test]
+async fn watch_closed() {
+    let (tx, rx) = watch::channel(0);
+    let rx2 = rx.clone();
+    let task = spawn(async move {
+        tx.closed().await;
+        tx.send(1)
+    });
+    yield_now().await;
+    drop(rx);
+    yield_now().await;
+    assert!(!task.is_finished());
+    drop(rx2);
+    assert!(task.await.unwrap().is_err());
+}
@@ -0,0 +1,153 @@
+use std::{cell::Cell, rc::Rc};
+
+use compio_runtime::{task::Builder, JoinSet, RuntimeBuilder, UnhandledPanic};
+
+#[compio_macros::test]
+async fn join_set() {
+    let mut set = JoinSet::new();
+    for i in 0..10 {
+        set.spawn(async move {
+            for _ in 0..i {
+                compio_runtime::yield_now().await;
+            }
+            i
+        });
+    }
+    assert_eq!(set.len(), 10);
+    let mut outputs = vec![];
+    while let Some(res) = set.join_next().await {
+        outputs.push(res.unwrap());
+    }
+    outputs.sort_unstable();
+    assert_eq!(outputs, (0..10).collect::<Vec<_>>());
+    assert!(set.join_next().await.is_none());
+}
+
+#[compio_macros::test]
+async fn join_set_panic() {
+    let mut set = JoinSet::new();
+    set.spawn(async { panic!("task panic") });
+    set.spawn(async { 1 });
+    let mut panics = 0;
+    let mut outputs = vec![];
+    while let Some(res) = set.join_next().await {
+        match res {
+            Ok(output) => outputs.push(output),
+            Err(e) => {
+                assert!(e.is_panic());
+                assert_eq!(*e.into_panic().downcast::<&str>().unwrap(), "task panic");
+                panics += 1;
+            }
+        }
+    }
+    assert_eq!(panics, 1);
+    assert_eq!(outputs, [1]);
+}
+
+#[compio_macros::test]
+async fn join_set_abort_on_drop() {
+    let finished = Rc::new(Cell::new(false));
+    let mut set = JoinSet::new();
+    set.spawn({
+        let finished = finished.clone();
+        async move {
+            compio_runtime::yield_now().await;
+            finished.set(true);
+        }
+    });
+    drop(set);
+    for _ in 0..4 {
+        compio_runtime::yield_now().await;
+    }
+    assert!(!finished.get());
+}
+
+#[compio_macros::test]
+async fn scope() {
+    let cell = Cell::new(0);
+    let output = compio_runtime::scope(|s| {
+        let cell = &cell;
+        async move {
+            for i in 1..=10 {
+                s.spawn(async move {
+                    compio_runtime::yield_now().await;
+                    cell.set(cell.get() + i);
+                });
+            }
+            let nested = s.clone();
+            s.spawn(async move { nested.spawn(async move { cell.get() }).await })
+                .await;
+            "done"
+        }
+    })
+    .await;
+    assert_eq!(output, "done");
+    assert_eq!(cell.get(), 55);
+}
+
+#[compio_macros::test]
+async fn panic_isolation() {
+    let task = compio_runtime::spawn(async { panic!("task panic") });
+    let err = task.await.unwrap_err();
+    assert!(err.is_panic());
+    assert_eq!(err.to_string(), "task panicked with message \"task panic\"");
+
+    // The runtime keeps running other tasks.
+    assert_eq!(compio_runtime::spawn(async { 42 }).await.unwrap(), 42);
+}
+
+#[compio_macros::test]
+async fn named_task() {
+    let task = Builder::new().name("worker").spawn(async { 1 });
+    assert_eq!(task.name(), Some("worker"));
+    assert_eq!(task.await.unwrap(), 1);
+
+    let task = compio_runtime::spawn(async {});
+    assert_eq!(task.name(), None);
+    task.await.unwrap();
+}
+
+#[test]
+fn shutdown_on_panic() {
+    let err = std::thread::spawn(|| {
+        RuntimeBuilder::new()
+            .unhandled_panic(UnhandledPanic::ShutdownRuntime)
+            .apply();
+        compio_runtime::block_on(async {
+            Builder::new()
+                .name("worker")
+                .spawn(async { panic!("task panic") })
+                .detach();
+            std::future::pending::<()>().await
+        })
+    })
+    .join()
+    .unwrap_err();
+    assert_eq!(
+        *err.downcast::<String>().unwrap(),
+        "task `worker` panicked, shutting down the runtime"
+    );
+}
+
+#[test]
+fn shutdown_on_panic_handled() {
+    std::thread::spawn(|| {
+        RuntimeBuilder::new()
+            .unhandled_panic(UnhandledPanic::ShutdownRuntime)
+            .apply();
+        compio_runtime::block_on(async {
+            // Observed by the handle.
+            let err = compio_runtime::spawn(async { panic!("task panic") })
+                .await
+                .unwrap_err();
+            assert!(err.is_panic());
+
+            // Observed by the join set.
+            let mut set = JoinSet::new();
+            set.spawn(async { panic!("task panic") });
+            assert!(set.join_next().await.unwrap().unwrap_err().is_panic());
+        })
+    })
+    .join()
+    .unwrap();
+}
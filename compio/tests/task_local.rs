@@ -0,0 +1,69 @@
+use std::cell::Cell;
+
+compio_runtime::task_local! {
+    static ID: u32;
+    static NAME: String;
+}
+
+#[compio_macros::test]
+async fn scope() {
+    assert!(ID.try_with(|_| ()).is_err());
+
+    ID.scope(1, async {
+        assert_eq!(ID.get(), 1);
+        compio_runtime::yield_now().await;
+        assert_eq!(ID.get(), 1);
+        ID.scope(2, async { assert_eq!(ID.get(), 2) }).await;
+        assert_eq!(ID.get(), 1);
+    })
+    .await;
+
+    assert!(ID.try_with(|_| ()).is_err());
+}
+
+#[compio_macros::test]
+async fn spawn() {
+    let tasks = (0..4)
+        .map(|i| {
+            compio_runtime::spawn(ID.scope(i, async move {
+                for _ in 0..4 {
+                    compio_runtime::yield_now().await;
+                    assert_eq!(ID.get(), i);
+                }
+            }))
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[test]
+fn sync_scope() {
+    let len = NAME.sync_scope("compio".to_string(), || NAME.with(|name| name.len()));
+    assert_eq!(len, 6);
+    assert!(NAME.try_with(|_| ()).is_err());
+}
+
+#[test]
+fn drop_in_scope() {
+    struct Guard;
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            DROPPED.with(|dropped| dropped.set(ID.try_with(|id| *id).ok()));
+        }
+    }
+
+    thread_local! {
+        static DROPPED: Cell<Option<u32>> = const { Cell::new(None) };
+    }
+
+    let guard = Guard;
+    let future = ID.scope(3, async move {
+        let _guard = guard;
+        std::future::pending::<()>().await
+    });
+    drop(future);
+    assert_eq!(DROPPED.with(Cell::get), Some(3));
+}
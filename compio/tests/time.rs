@@ -0,0 +1,224 @@
+use std::{
+    cell::RefCell,
+    future::{poll_fn, Future},
+    pin::pin,
+    rc::Rc,
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use compio::runtime::time::{
+    advance, interval, now, pause, resume, sleep, timeout, MissedTickBehavior,
+};
+use futures_util::{stream::FuturesUnordered, StreamExt};
+
+#[compio_macros::test]
+async fn sleep_order() {
+    let order = Rc::new(RefCell::new(vec![]));
+    let mut tasks = [50u64, 10, 200, 30, 100]
+        .into_iter()
+        .map(|ms| {
+            let order = order.clone();
+            async move {
+                let start = Instant::now();
+                sleep(Duration::from_millis(ms)).await;
+                assert!(start.elapsed() >= Duration::from_millis(ms));
+                order.borrow_mut().push(ms);
+            }
+        })
+        .collect::<FuturesUnordered<_>>();
+    while tasks.next().await.is_some() {}
+    assert_eq!(*order.borrow(), [10, 30, 50, 100, 200]);
+}
+
+#[compio_macros::test]
+async fn cancelled_key_reused() {
+    // Register a short timer and cancel it.
+    {
+        let mut short = pin!(sleep(Duration::from_millis(10)));
+        poll_fn(|cx| {
+            assert!(short.as_mut().poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+    }
+    // The new timer reuses the slab entry, and should not fire early.
+    let start = Instant::now();
+    sleep(Duration::from_millis(100)).await;
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[compio_macros::test]
+async fn many_cancelled() {
+    for i in 1..=10000 {
+        let mut timer = pin!(sleep(Duration::from_secs(i)));
+        poll_fn(|cx| {
+            assert!(timer.as_mut().poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await;
+    }
+    let start = Instant::now();
+    sleep(Duration::from_millis(10)).await;
+    assert!(start.elapsed() >= Duration::from_millis(10));
+}
+
+#[compio_macros::test]
+async fn long_timeout() {
+    let res = timeout(
+        Duration::from_secs(60 * 60 * 24 * 365 * 10),
+        sleep(Duration::from_millis(70)),
+    )
+    .await;
+    assert!(res.is_ok());
+
+    let res = timeout(Duration::from_millis(70), std::future::pending::<()>()).await;
+    assert!(res.is_err());
+}
+
+const PERIOD: Duration = Duration::from_millis(50);
+
+// Ticks once, and block the thread for 3.5 periods to miss ticks.
+async fn slow_consumer(behavior: MissedTickBehavior) -> (Instant, Vec<Instant>) {
+    let mut interval = interval(PERIOD);
+    interval.set_missed_tick_behavior(behavior);
+    let start = interval.tick().await;
+    std::thread::sleep(PERIOD * 7 / 2);
+    let mut ticks = vec![];
+    for _ in 0..4 {
+        ticks.push(interval.tick().await);
+    }
+    (start, ticks)
+}
+
+#[compio_macros::test]
+async fn interval_burst() {
+    let (start, ticks) = slow_consumer(MissedTickBehavior::Burst).await;
+    let expected = (1..=4).map(|i| start + PERIOD * i).collect::<Vec<_>>();
+    assert_eq!(ticks, expected);
+}
+
+#[compio_macros::test]
+async fn interval_delay() {
+    let (start, ticks) = slow_consumer(MissedTickBehavior::Delay).await;
+    assert_eq!(ticks[0], start + PERIOD);
+    assert!(ticks[1] >= start + PERIOD * 9 / 2);
+    assert_eq!(ticks[2], ticks[1] + PERIOD);
+    assert_eq!(ticks[3], ticks[2] + PERIOD);
+}
+
+#[compio_macros::test]
+async fn interval_skip() {
+    let (start, ticks) = slow_consumer(MissedTickBehavior::Skip).await;
+    assert_eq!(ticks[0], start + PERIOD);
+    assert_eq!(ticks[1], start + PERIOD * 4);
+    assert_eq!(ticks[2], start + PERIOD * 5);
+    assert_eq!(ticks[3], start + PERIOD * 6);
+}
+
+#[compio_macros::test]
+async fn interval_reset() {
+    let mut interval = interval(PERIOD);
+    let start = interval.tick().await;
+    interval.reset_at(start + PERIOD * 3);
+    assert_eq!(interval.tick().await, start + PERIOD * 3);
+    assert_eq!(interval.tick().await, start + PERIOD * 4);
+
+    let now = Instant::now();
+    interval.reset();
+    assert!(interval.tick().await >= now + PERIOD);
+}
+
+#[compio_macros::test]
+async fn interval_send() {
+    let mut interval = interval(PERIOD);
+    let start = interval.tick().await;
+    // Register the timer of the next tick, and move it through another thread.
+    assert!(poll_fn(|cx| Poll::Ready(interval.poll_tick(cx)))
+        .await
+        .is_pending());
+    let mut interval = std::thread::spawn(move || interval).join().unwrap();
+    assert_eq!(interval.tick().await, start + PERIOD);
+}
+
+#[compio_macros::test]
+async fn interval_stream() {
+    let start = Instant::now();
+    let ticks = interval(PERIOD).take(3).collect::<Vec<_>>().await;
+    assert_eq!(ticks.len(), 3);
+    assert_eq!(ticks[1] - ticks[0], PERIOD);
+    assert_eq!(ticks[2] - ticks[1], PERIOD);
+    assert!(start.elapsed() >= PERIOD * 2);
+}
+
+#[compio_macros::test]
+async fn paused_auto_advance() {
+    pause();
+    let real = Instant::now();
+    let start = now();
+
+    sleep(Duration::from_secs(60)).await;
+    assert_eq!(now() - start, Duration::from_secs(60));
+
+    let res = timeout(Duration::from_secs(60), std::future::pending::<()>()).await;
+    assert!(res.is_err());
+    assert_eq!(now() - start, Duration::from_secs(120));
+
+    let ticks = interval(Duration::from_secs(60))
+        .take(3)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(ticks[2] - ticks[0], Duration::from_secs(120));
+
+    assert!(real.elapsed() < Duration::from_secs(10));
+}
+
+#[cfg(feature = "event")]
+#[compio_macros::test]
+async fn paused_blocking() {
+    pause();
+    let start = now();
+
+    // The paused clock is not advanced while the blocking job is running.
+    let res = timeout(
+        Duration::from_secs(1),
+        compio::runtime::spawn_blocking(|| {
+            std::thread::sleep(Duration::from_millis(50));
+            42
+        }),
+    )
+    .await;
+    assert_eq!(res.unwrap(), 42);
+    assert_eq!(now(), start);
+}
+
+#[compio_macros::test]
+async fn paused_advance() {
+    pause();
+    let fired = Rc::new(RefCell::new(vec![]));
+    let tasks = [10u64, 20, 30]
+        .into_iter()
+        .map(|secs| {
+            let fired = fired.clone();
+            compio::runtime::spawn(async move {
+                sleep(Duration::from_secs(secs)).await;
+                fired.borrow_mut().push(secs);
+            })
+        })
+        .collect::<Vec<_>>();
+    // Let the tasks register the timers.
+    compio::runtime::yield_now().await;
+
+    advance(Duration::from_secs(15)).await;
+    assert_eq!(*fired.borrow(), [10]);
+    advance(Duration::from_secs(15)).await;
+    assert_eq!(*fired.borrow(), [10, 20, 30]);
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    resume();
+    let start = now();
+    sleep(Duration::from_millis(10)).await;
+    assert!(now() - start >= Duration::from_millis(10));
+}